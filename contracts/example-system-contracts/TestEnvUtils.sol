@@ -29,4 +29,40 @@ contract TestEnvUtils {
     function ecdsa_recover(uint8[65] calldata signature, bytes32 message_hash) external view returns(bool, uint8[33] memory) {
         return sys_contract.ecdsa_recover(signature, message_hash);
     }
+
+    function ecdsa_verify(uint8[65] calldata signature, bytes32 message_hash, uint8[33] calldata pubkey) external view returns(bool) {
+        return sys_contract.ecdsa_verify(signature, message_hash, pubkey);
+    }
+
+    function blake2_256(bytes calldata input) external view returns(bytes32) {
+        return sys_contract.blake2_256(input);
+    }
+
+    function keccak_256(bytes calldata input) external view returns(bytes32) {
+        return sys_contract.keccak_256(input);
+    }
+
+    function sha2_256(bytes calldata input) external view returns(bytes32) {
+        return sys_contract.sha2_256(input);
+    }
+
+    function recover_eth_address(uint8[65] calldata signature, bytes32 message_hash) external view returns(bool, bytes20) {
+        return sys_contract.recover_eth_address(signature, message_hash);
+    }
+
+    function eth_message_hash(bytes calldata message) external view returns(bytes32) {
+        return sys_contract.eth_message_hash(message);
+    }
+
+    function personal_recover(bytes calldata message, uint8[65] calldata signature) external view returns(bool, bytes20) {
+        return sys_contract.personal_recover(message, signature);
+    }
+
+    function eip712_digest(bytes32 domain_separator, bytes32 struct_hash) external view returns(bytes32) {
+        return sys_contract.eip712_digest(domain_separator, struct_hash);
+    }
+
+    function default_account_id(bytes20 eth_address) external view returns(address) {
+        return sys_contract.default_account_id(eth_address);
+    }
 }
@@ -0,0 +1,190 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Escrow of native tokens, composing the `native_token_wrapper` system-contract through
+//! `NativeTokenRef`.
+//!
+//! The payer approves the escrow on the wrapper and deposits, the tokens are then held by the
+//! escrow until either the payer or the arbiter releases them to the payee, or either the payee or
+//! the arbiter refunds them to the payer. Whatever the wrapper refuses a transfer with is passed on
+//! as `Error::Token`.
+
+use ink_lang as ink;
+
+#[ink::contract(env = laguna_env::LagunaEnvironment)]
+mod native_token_escrow {
+	use ethereum_types::U256;
+	use ink_storage::{
+		traits::{PackedLayout, SpreadAllocate, SpreadLayout},
+		Mapping,
+	};
+	use laguna_env::ExtensionError;
+	use native_token_wrapper::{Error as TokenError, NativeTokenRef};
+
+	/// Tokens held on behalf of `payer` until they go to `payee`
+	#[derive(
+		scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Clone, Debug, PartialEq, Eq,
+	)]
+	#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+	pub struct Deal {
+		pub payer: AccountId,
+		pub payee: AccountId,
+		pub arbiter: AccountId,
+		pub value: Balance,
+	}
+
+	#[derive(scale::Encode, scale::Decode, Debug, PartialEq, Eq)]
+	#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+	pub enum Error {
+		/// No open deal under this id
+		UnknownDeal,
+		/// The caller is not a party allowed to settle the deal this way
+		NotAllowed,
+		/// The value doesn't fit a native balance
+		BalanceOverflow,
+		/// The wrapper refused to move the tokens
+		Token(TokenError),
+	}
+
+	impl From<TokenError> for Error {
+		fn from(e: TokenError) -> Self {
+			Error::Token(e)
+		}
+	}
+
+	#[ink(storage)]
+	#[derive(SpreadAllocate)]
+	pub struct NativeTokenEscrow {
+		/// `native_token_wrapper` of the escrowed token
+		token: AccountId,
+		/// Open deals by id
+		deals: Mapping<u32, Deal>,
+		next_id: u32,
+	}
+
+	/// Event emitted when a payer deposits tokens into the escrow.
+	#[ink(event)]
+	pub struct Deposited {
+		#[ink(topic)]
+		id: u32,
+		#[ink(topic)]
+		payer: AccountId,
+		#[ink(topic)]
+		payee: AccountId,
+		value: U256,
+	}
+
+	/// Event emitted when the tokens of a deal go to the payee.
+	#[ink(event)]
+	pub struct Released {
+		#[ink(topic)]
+		id: u32,
+	}
+
+	/// Event emitted when the tokens of a deal go back to the payer.
+	#[ink(event)]
+	pub struct Refunded {
+		#[ink(topic)]
+		id: u32,
+	}
+
+	impl NativeTokenEscrow {
+		/// Creates an escrow of the native token wrapped by the `native_token_wrapper` at `token`
+		#[ink(constructor)]
+		pub fn new(token: AccountId) -> Self {
+			ink_lang::utils::initialize_contract(|contract: &mut Self| contract.token = token)
+		}
+
+		/// Returns the wrapper of the escrowed token
+		#[ink(message)]
+		pub fn token(&self) -> AccountId {
+			self.token
+		}
+
+		/// Returns the open deal `id`, if any
+		#[ink(message)]
+		pub fn deal(&self, id: u32) -> Option<Deal> {
+			self.deals.get(id)
+		}
+
+		/// Moves `value` tokens from the caller into the escrow, to be paid to `payee` once
+		/// released, and returns the id of the deal.
+		///
+		/// The caller must have approved the escrow for at least `value` on the wrapper. A
+		/// `Deposited` event is emitted.
+		#[ink(message)]
+		pub fn deposit(
+			&mut self,
+			payee: AccountId,
+			arbiter: AccountId,
+			value: U256,
+		) -> Result<u32, Error> {
+			let payer = self.env().caller();
+			let escrow = self.env().account_id();
+			let balance = u128::try_from(value).map_err(|_| Error::BalanceOverflow)?;
+
+			// a reverted call traps the caller in ink! 3, so the reasons the wrapper would revert
+			// with are read from its views up front
+			let mut token = self.wrapper();
+			if token.allowance(payer, escrow) < value {
+				return Err(TokenError::InsufficientAllowance.into())
+			}
+			if token.balance_of(payer) < value {
+				return Err(TokenError::Extension(ExtensionError::InsufficientBalance).into())
+			}
+			token.try_transfer_from(payer, escrow, value);
+
+			let id = self.next_id;
+			self.next_id += 1;
+			self.deals.insert(id, &Deal { payer, payee, arbiter, value: balance });
+			self.env().emit_event(Deposited { id, payer, payee, value });
+			Ok(id)
+		}
+
+		/// Pays the tokens of deal `id` to its payee, only the payer or the arbiter may do so.
+		///
+		/// A `Released` event is emitted.
+		#[ink(message)]
+		pub fn release(&mut self, id: u32) -> Result<(), Error> {
+			let deal =
+				self.settle(id, |deal, caller| caller == deal.payer || caller == deal.arbiter)?;
+			self.pay(deal.payee, deal.value);
+			self.env().emit_event(Released { id });
+			Ok(())
+		}
+
+		/// Pays the tokens of deal `id` back to its payer, only the payee or the arbiter may do
+		/// so.
+		///
+		/// A `Refunded` event is emitted.
+		#[ink(message)]
+		pub fn refund(&mut self, id: u32) -> Result<(), Error> {
+			let deal =
+				self.settle(id, |deal, caller| caller == deal.payee || caller == deal.arbiter)?;
+			self.pay(deal.payer, deal.value);
+			self.env().emit_event(Refunded { id });
+			Ok(())
+		}
+
+		fn wrapper(&self) -> NativeTokenRef {
+			ink_env::call::FromAccountId::from_account_id(self.token)
+		}
+
+		/// Closes deal `id` if `allowed` lets the caller settle it
+		fn settle(
+			&mut self,
+			id: u32,
+			allowed: impl Fn(&Deal, AccountId) -> bool,
+		) -> Result<Deal, Error> {
+			let deal = self.deals.get(id).ok_or(Error::UnknownDeal)?;
+			if !allowed(&deal, self.env().caller()) {
+				return Err(Error::NotAllowed)
+			}
+			self.deals.remove(id);
+			Ok(deal)
+		}
+
+		fn pay(&self, to: AccountId, value: Balance) {
+			self.wrapper().try_transfer(to, U256::from(value))
+		}
+	}
+}
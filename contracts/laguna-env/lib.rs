@@ -0,0 +1,174 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! The laguna chain extension, its error mapping and the environment using it, shared by every
+//! ink! system-contract so none of them has to copy extension ids.
+
+use ink_env::{AccountId, Environment};
+use ink_lang as ink;
+use ink_prelude::vec::Vec;
+use scale::{Decode, Encode};
+
+/// Well-known address of the `env_utils` system-contract
+pub const ENV_UTILS: [u8; 32] = [0xff; 32];
+
+/// Failure reported by the runtime through the status code of an extension
+#[derive(Encode, Decode, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum ExtensionError {
+	InvalidTokenId,
+	InsufficientBalance,
+	AccessDenied,
+	UnknownStatusCode,
+	InvalidScaleEncoding,
+	/// The transfer would leave an account below the existential deposit
+	BelowExistentialDeposit,
+	/// The account is frozen and can't send tokens
+	AccountFrozen,
+	/// A balance or the total supply would overflow
+	ArithmeticOverflow,
+	/// The token can't be transferred at all
+	NotTransferable,
+}
+
+impl From<scale::Error> for ExtensionError {
+	fn from(_: scale::Error) -> Self {
+		ExtensionError::InvalidScaleEncoding
+	}
+}
+
+impl ink_env::chain_extension::FromStatusCode for ExtensionError {
+	fn from_status_code(status_code: u32) -> Result<(), Self> {
+		match status_code {
+			0 => Ok(()),
+			1 => Err(Self::InvalidTokenId),
+			2 => Err(Self::InsufficientBalance),
+			3 => Err(Self::BelowExistentialDeposit),
+			4 => Err(Self::AccountFrozen),
+			5 => Err(Self::ArithmeticOverflow),
+			6 => Err(Self::NotTransferable),
+			403 => Err(Self::AccessDenied),
+			_ => Err(Self::UnknownStatusCode),
+		}
+	}
+}
+
+/// Native token balances
+pub type Balance = <ink_env::DefaultEnvironment as Environment>::Balance;
+
+/// Every function the laguna runtime exposes to contracts, by extension id
+#[ink::chain_extension]
+pub trait LagunaExtension {
+	type ErrorCode = ExtensionError;
+
+	/// Allows the calling contract to use the privileged extensions, root only
+	#[ink(extension = 10, returns_result = false)]
+	fn whitelist_contract();
+
+	/// `InvalidTokenId` unless a native token `token_id` exists
+	#[ink(extension = 200, returns_result = false)]
+	fn is_valid_token(token_id: u32);
+
+	#[ink(extension = 201)]
+	fn name(token_id: u32) -> Result<Vec<u8>, ExtensionError>;
+
+	#[ink(extension = 202)]
+	fn symbol(token_id: u32) -> Result<Vec<u8>, ExtensionError>;
+
+	#[ink(extension = 203)]
+	fn decimals(token_id: u32) -> Result<u8, ExtensionError>;
+
+	#[ink(extension = 204)]
+	fn total_supply(token_id: u32) -> Result<Balance, ExtensionError>;
+
+	#[ink(extension = 205)]
+	fn balance_of(token_id: u32, owner: AccountId) -> Result<Balance, ExtensionError>;
+
+	#[ink(extension = 206, returns_result = false)]
+	fn transfer(token_id: u32, to: AccountId, value: Balance);
+
+	#[ink(extension = 207, returns_result = false)]
+	fn transfer_from(token_id: u32, from: AccountId, to: AccountId, value: Balance);
+}
+
+/// Role-gated supply management, only built with the `mint-burn` feature for runtimes providing
+/// extensions 210 and 211
+#[cfg(feature = "mint-burn")]
+pub mod supply {
+	use super::{Balance, ExtensionError};
+	use ink_env::{chain_extension::ChainExtensionMethod, AccountId};
+
+	/// Issues `value` new tokens to `to`, `AccessDenied` unless `origin` holds the minter role
+	pub fn mint(
+		token_id: u32,
+		origin: AccountId,
+		to: AccountId,
+		value: Balance,
+	) -> Result<(), ExtensionError> {
+		ChainExtensionMethod::build(210)
+			.input::<(u32, AccountId, AccountId, Balance)>()
+			.output::<()>()
+			.handle_error_code::<ExtensionError>()
+			.call(&(token_id, origin, to, value))
+	}
+
+	/// Destroys `value` tokens of `from`, `AccessDenied` unless `origin` holds the burner role
+	pub fn burn(
+		token_id: u32,
+		origin: AccountId,
+		from: AccountId,
+		value: Balance,
+	) -> Result<(), ExtensionError> {
+		ChainExtensionMethod::build(211)
+			.input::<(u32, AccountId, AccountId, Balance)>()
+			.output::<()>()
+			.handle_error_code::<ExtensionError>()
+			.call(&(token_id, origin, from, value))
+	}
+}
+
+/// Environment of contracts calling into `LagunaExtension`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum LagunaEnvironment {}
+
+impl Environment for LagunaEnvironment {
+	const MAX_EVENT_TOPICS: usize = <ink_env::DefaultEnvironment as Environment>::MAX_EVENT_TOPICS;
+
+	type AccountId = <ink_env::DefaultEnvironment as Environment>::AccountId;
+	type Balance = <ink_env::DefaultEnvironment as Environment>::Balance;
+	type Hash = <ink_env::DefaultEnvironment as Environment>::Hash;
+	type BlockNumber = <ink_env::DefaultEnvironment as Environment>::BlockNumber;
+	type Timestamp = <ink_env::DefaultEnvironment as Environment>::Timestamp;
+
+	type ChainExtension = LagunaExtension;
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ink_env::chain_extension::FromStatusCode;
+
+	#[test]
+	fn status_codes_map_to_errors() {
+		assert!(ExtensionError::from_status_code(0).is_ok());
+		assert!(matches!(ExtensionError::from_status_code(1), Err(ExtensionError::InvalidTokenId)));
+		assert!(matches!(
+			ExtensionError::from_status_code(2),
+			Err(ExtensionError::InsufficientBalance)
+		));
+		assert!(matches!(ExtensionError::from_status_code(403), Err(ExtensionError::AccessDenied)));
+		assert!(matches!(
+			ExtensionError::from_status_code(7),
+			Err(ExtensionError::UnknownStatusCode)
+		));
+
+		for (status, error) in [
+			(3, ExtensionError::BelowExistentialDeposit),
+			(4, ExtensionError::AccountFrozen),
+			(5, ExtensionError::ArithmeticOverflow),
+			(6, ExtensionError::NotTransferable),
+		] {
+			assert_eq!(ExtensionError::from_status_code(status), Err(error));
+		}
+	}
+}
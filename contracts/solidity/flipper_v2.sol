@@ -0,0 +1,25 @@
+contract flipper_v2 {
+	bool private value;
+
+	/// Same storage layout as `flipper`, so an existing flipper can be upgraded to it.
+	constructor(bool initvalue) {
+		value = initvalue;
+	}
+
+	function flip() public {
+		value = !value;
+	}
+
+	function get() public view returns (bool) {
+		return value;
+	}
+
+	/// Explicitly set the stored value, only available after the upgrade.
+	function set(bool newvalue) public {
+		value = newvalue;
+	}
+
+	function version() public pure returns (uint32) {
+		return 2;
+	}
+}
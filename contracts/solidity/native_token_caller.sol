@@ -0,0 +1,26 @@
+import "../system-contracts/native_token_wrapper/interface.sol";
+
+// calls the try* messages of a native token wrapper and hands back the reason they reverted with
+contract native_token_caller {
+	NativeToken token;
+
+	constructor(NativeToken _token) {
+		token = _token;
+	}
+
+	function try_transfer(address to, uint256 value) public returns (string memory) {
+		try token.tryTransfer(to, value) {
+			return "";
+		} catch Error(string memory reason) {
+			return reason;
+		}
+	}
+
+	function try_transfer_from(address from, address to, uint256 value) public returns (string memory) {
+		try token.tryTransferFrom(from, to, value) {
+			return "";
+		} catch Error(string memory reason) {
+			return reason;
+		}
+	}
+}
@@ -19,4 +19,34 @@ interface EnvUtils {
     /// Recovers the compressed ECDSA public key for given signature and message_hash
     /// Incase of error, (false, bytes33(0)) is returned
     function ecdsa_recover(uint8[65] calldata signature, bytes32 message_hash) external view returns(bool, uint8[33] memory);
+
+    /// Checks a recoverable secp256k1 signature of the message_hash against the compressed public key
+    function ecdsa_verify(uint8[65] calldata signature, bytes32 message_hash, uint8[33] calldata pubkey) external view returns(bool);
+
+    /// Hashes the input with blake2b-256
+    function blake2_256(bytes calldata input) external view returns(bytes32);
+
+    /// Hashes the input with keccak-256
+    function keccak_256(bytes calldata input) external view returns(bytes32);
+
+    /// Hashes the input with sha2-256
+    function sha2_256(bytes calldata input) external view returns(bytes32);
+
+    /// Recovers the Ethereum address which signed the message_hash, in a single call
+    /// Incase of error, (false, bytes20(0)) is returned
+    function recover_eth_address(uint8[65] calldata signature, bytes32 message_hash) external view returns(bool, bytes20);
+
+    /// EIP-191 hash of a message signed with personal_sign
+    function eth_message_hash(bytes calldata message) external view returns(bytes32);
+
+    /// Recovers the Ethereum address which signed the message with personal_sign
+    /// Incase of error, (false, bytes20(0)) is returned
+    function personal_recover(bytes calldata message, uint8[65] calldata signature) external view returns(bool, bytes20);
+
+    /// EIP-712 digest of a typed struct from its domain separator and struct hash
+    function eip712_digest(bytes32 domain_separator, bytes32 struct_hash) external view returns(bytes32);
+
+    /// Returns the default account the runtime derives for the Ethereum address, blake2_256("evm:" ++ eth_address)
+    /// Addresses with a proxy account registered in EvmCompat are mapped to it instead
+    function default_account_id(bytes20 eth_address) external view returns(address);
 }
@@ -2,8 +2,10 @@
 
 use ink_lang as ink;
 
-#[ink::contract]
+#[ink::contract(env = laguna_env::LagunaEnvironment)]
 mod env_utils {
+    use ink_env::hash::{Blake2x256, HashOutput, Keccak256, Sha2x256};
+    use ink_prelude::vec::Vec;
 
     #[ink(storage)]
     pub struct EnvUtils {}
@@ -53,5 +55,104 @@ mod env_utils {
                 Err(_) => (false, [0u8; 33]),
             }
         }
+
+        /// Checks the recoverable secp256k1 `signature` of `message_hash` against the
+        /// compressed public key `pubkey`
+        #[ink(message, selector = 0xc176b860)]
+        pub fn ecdsa_verify(
+            &self,
+            signature: [u8; 65],
+            message_hash: [u8; 32],
+            pubkey: [u8; 33],
+        ) -> bool {
+            self.ecdsa_recover(signature, message_hash) == (true, pubkey)
+        }
+
+        #[ink(message, selector = 0x76ff2a80)]
+        pub fn blake2_256(&self, input: Vec<u8>) -> [u8; 32] {
+            hash::<Blake2x256>(&input)
+        }
+
+        #[ink(message, selector = 0xabc4bd65)]
+        pub fn keccak_256(&self, input: Vec<u8>) -> [u8; 32] {
+            hash::<Keccak256>(&input)
+        }
+
+        #[ink(message, selector = 0x743e8417)]
+        pub fn sha2_256(&self, input: Vec<u8>) -> [u8; 32] {
+            hash::<Sha2x256>(&input)
+        }
+
+        /// Recovers the ethereum address which signed `message_hash`, in a single call.
+        /// Incase of error, (false, [0u8; 20]) is returned
+        #[ink(message, selector = 0xbc9acdc1)]
+        pub fn recover_eth_address(
+            &self,
+            signature: [u8; 65],
+            message_hash: [u8; 32],
+        ) -> (bool, [u8; 20]) {
+            match self.ecdsa_recover(signature, message_hash) {
+                (true, pubkey) => self.ecdsa_to_eth_address(pubkey),
+                _ => (false, [0u8; 20]),
+            }
+        }
+
+        /// EIP-191 hash of `message` as signed by `personal_sign`:
+        /// keccak256("\x19Ethereum Signed Message:\n" ++ len(message) ++ message)
+        #[ink(message, selector = 0x8875b9c9)]
+        pub fn eth_message_hash(&self, message: Vec<u8>) -> [u8; 32] {
+            let mut prefixed = Vec::with_capacity(32 + message.len());
+            prefixed.extend_from_slice(b"\x19Ethereum Signed Message:\n");
+            prefixed.extend_from_slice(&decimal(message.len()));
+            prefixed.extend_from_slice(&message);
+            hash::<Keccak256>(&prefixed)
+        }
+
+        /// Recovers the ethereum address which `personal_sign`ed `message`
+        #[ink(message, selector = 0x63704a96)]
+        pub fn personal_recover(&self, message: Vec<u8>, signature: [u8; 65]) -> (bool, [u8; 20]) {
+            self.recover_eth_address(signature, self.eth_message_hash(message))
+        }
+
+        /// EIP-712 digest of a typed struct: keccak256("\x19\x01" ++ domain_separator ++ struct_hash)
+        #[ink(message, selector = 0x0ae82687)]
+        pub fn eip712_digest(&self, domain_separator: [u8; 32], struct_hash: [u8; 32]) -> [u8; 32] {
+            let mut encoded = Vec::with_capacity(2 + 2 * 32);
+            encoded.extend_from_slice(b"\x19\x01");
+            encoded.extend_from_slice(&domain_separator);
+            encoded.extend_from_slice(&struct_hash);
+            hash::<Keccak256>(&encoded)
+        }
+
+        /// Returns the default account the runtime derives for the ethereum `address`:
+        /// blake2_256("evm:" ++ address). Addresses with a proxy account registered in
+        /// `EvmCompat` are mapped to it instead, which contracts can't look up.
+        #[ink(message, selector = 0xab7b233e)]
+        pub fn default_account_id(&self, address: [u8; 20]) -> AccountId {
+            let mut input = Vec::with_capacity(4 + 20);
+            input.extend_from_slice(b"evm:");
+            input.extend_from_slice(&address);
+            hash::<Blake2x256>(&input).into()
+        }
+    }
+
+    /// ASCII decimal representation of `n`
+    fn decimal(mut n: usize) -> Vec<u8> {
+        let mut digits = Vec::new();
+        loop {
+            digits.push(b'0' + (n % 10) as u8);
+            n /= 10;
+            if n == 0 {
+                break;
+            }
+        }
+        digits.reverse();
+        digits
+    }
+
+    fn hash<H: HashOutput<Type = [u8; 32]>>(input: &[u8]) -> [u8; 32] {
+        let mut output = [0u8; 32];
+        ink_env::hash_bytes::<H>(input, &mut output);
+        output
     }
 }
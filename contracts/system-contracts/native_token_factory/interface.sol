@@ -0,0 +1,18 @@
+// SPDX-License-Identifier: MIT
+
+pragma solidity ^0.8.0;
+
+interface NativeTokenFactory {
+    /// Code hash of the native_token_wrapper every wrapper is instantiated from.
+    function wrapper_code_hash() external view returns(bytes32);
+
+    /// Ids of the native tokens which already have a wrapper, in the order they were wrapped.
+    function wrapped_tokens() external view returns(uint32[] memory);
+
+    /// Retrieves the ERC-20 wrapper of a native token (if it exists)
+    /// Incase it doesn't, (false, address(0)) is returned
+    function get_wrapper(uint32 token_id) external view returns(bool, address);
+
+    /// Retrieves the ERC-20 wrapper of a native token, instantiating it on first use.
+    function wrap(uint32 token_id) external returns(address);
+}
@@ -0,0 +1,89 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use ink_lang as ink;
+
+#[ink::contract(env = laguna_env::LagunaEnvironment)]
+mod native_token_factory {
+	use ink_prelude::vec::Vec;
+	use ink_storage::{traits::SpreadAllocate, Mapping};
+	use native_token_wrapper::NativeTokenRef;
+
+	#[ink(storage)]
+	#[derive(SpreadAllocate)]
+	pub struct NativeTokenFactory {
+		/// Code hash of the uploaded `native_token_wrapper`
+		wrapper_code_hash: Hash,
+		/// ERC-20 wrapper instantiated for each native token id
+		wrappers: Mapping<u32, AccountId>,
+		/// Ids of the wrapped native tokens, in the order they were wrapped
+		wrapped: Vec<u32>,
+	}
+
+	/// Event emitted when the wrapper of a native token is instantiated.
+	#[ink(event)]
+	pub struct WrapperCreated {
+		#[ink(topic)]
+		token_id: u32,
+		wrapper: AccountId,
+	}
+
+	impl NativeTokenFactory {
+		/// Creates a factory instantiating wrappers from the `native_token_wrapper` code
+		/// uploaded under `wrapper_code_hash`
+		#[ink(constructor, selector = 0xf844af41)]
+		pub fn new(wrapper_code_hash: Hash) -> Self {
+			ink_lang::utils::initialize_contract(|contract: &mut Self| {
+				contract.wrapper_code_hash = wrapper_code_hash
+			})
+		}
+
+		/// Returns the code hash wrappers are instantiated from
+		#[ink(message, selector = 0x776d6618)]
+		pub fn wrapper_code_hash(&self) -> Hash {
+			self.wrapper_code_hash
+		}
+
+		/// Returns the ids of the native tokens wrapped so far, in the order they were wrapped
+		#[ink(message, selector = 0xf740ab7e)]
+		pub fn wrapped_tokens(&self) -> Vec<u32> {
+			self.wrapped.clone()
+		}
+
+		/// Returns the wrapper of `token_id` if it has been instantiated,
+		/// `(false, 0x0)` otherwise
+		#[ink(message, selector = 0x17772aae)]
+		pub fn get_wrapper(&self, token_id: u32) -> (bool, AccountId) {
+			match self.wrappers.get(token_id) {
+				Some(wrapper) => (true, wrapper),
+				None => (false, AccountId::from([0u8; 32])),
+			}
+		}
+
+		/// Returns the wrapper of `token_id`, instantiating it on first use.
+		///
+		/// A `WrapperCreated` event is emitted when a new wrapper is instantiated.
+		#[ink(message, selector = 0x9f284be7)]
+		pub fn wrap(&mut self, token_id: u32) -> AccountId {
+			if let Some(wrapper) = self.wrappers.get(token_id) {
+				return wrapper
+			}
+
+			if self.env().extension().is_valid_token(token_id).is_err() {
+				panic!("Invalid tokenId")
+			}
+
+			let wrapper = NativeTokenRef::create_wrapper_token(token_id)
+				.code_hash(self.wrapper_code_hash)
+				.endowment(0)
+				.salt_bytes(token_id.to_le_bytes())
+				.instantiate()
+				.expect("Failed to instantiate the wrapper");
+			let wrapper = ink_lang::ToAccountId::to_account_id(&wrapper);
+
+			self.wrappers.insert(token_id, &wrapper);
+			self.wrapped.push(token_id);
+			self.env().emit_event(WrapperCreated { token_id, wrapper });
+			wrapper
+		}
+	}
+}
@@ -0,0 +1,75 @@
+// SPDX-License-Identifier: MIT
+
+pragma solidity ^0.8.0;
+
+/// ERC-20 wrapper of a native token, `address` being a substrate account.
+///
+/// The try* variants, batch_transfer and permit revert with Error(string), the reason being the
+/// name of the failure, e.g. "InsufficientBalance" or "InsufficientAllowance".
+interface NativeToken {
+    struct Recipient {
+        address to;
+        uint256 value;
+    }
+
+    function name() external view returns(string memory);
+
+    function symbol() external view returns(string memory);
+
+    function decimals() external view returns(uint8);
+
+    function totalSupply() external view returns(uint256);
+
+    function balanceOf(address owner) external view returns(uint256);
+
+    function allowance(address owner, address spender) external view returns(uint256);
+
+    /// Returns false if the runtime refuses the transfer
+    function transfer(address to, uint256 value) external returns(bool);
+
+    function tryTransfer(address to, uint256 value) external;
+
+    /// Maximum number of recipients of a batch_transfer
+    function max_batch_size() external view returns(uint32);
+
+    /// Pays every recipient or none, reverts with "BatchTooLarge" beyond max_batch_size()
+    function batch_transfer(Recipient[] calldata recipients) external;
+
+    function approve(address spender, uint256 value) external returns(bool);
+
+    /// Returns false if the allowance would overflow
+    function increaseAllowance(address spender, uint256 addedValue) external returns(bool);
+
+    function tryIncreaseAllowance(address spender, uint256 addedValue) external;
+
+    /// Returns false if the allowance would drop below zero
+    function decreaseAllowance(address spender, uint256 subtractedValue) external returns(bool);
+
+    function tryDecreaseAllowance(address spender, uint256 subtractedValue) external;
+
+    /// EIP-712 domain separator of permits, bound to the chain id the wrapper was instantiated
+    /// with. Reverts with "UnknownChain" without one.
+    function DOMAIN_SEPARATOR() external view returns(bytes32);
+
+    function nonces(bytes20 owner) external view returns(uint256);
+
+    /// EIP-2612 permit of the ethereum address `owner`, which approves `spender` on behalf of
+    /// its default account. Reverts with "PermitExpired", "InvalidSignature" or "UnknownChain".
+    function permit(bytes20 owner, address spender, uint256 value, uint256 deadline, uint8 v, bytes32 r, bytes32 s) external;
+
+    /// An allowance of type(uint256).max is infinite and never decreases
+    function transferFrom(address from, address to, uint256 value) external returns(bool);
+
+    function tryTransferFrom(address from, address to, uint256 value) external;
+
+    /// Only callers holding the minter role of the token get through. Wrappers built without the
+    /// `mint-burn` feature, for runtimes lacking supply management, revert with "Unsupported".
+    function mint(address to, uint256 value) external returns(bool);
+
+    function tryMint(address to, uint256 value) external;
+
+    /// Only callers holding the burner role of the token get through, "Unsupported" as for mint
+    function burn(address from, uint256 value) external returns(bool);
+
+    function tryBurn(address from, uint256 value) external;
+}
@@ -1,96 +1,60 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use ink_env::{AccountId, Environment};
 use ink_lang as ink;
 use ink_prelude::vec::Vec as StorageVec;
-use scale::{Decode, Encode};
-
-#[derive(Encode, Decode, Debug)]
-#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
-pub enum ExtensionError {
-	InvalidTokenId,
-	InsufficientBalance,
-	AccessDenied,
-	UnknownStatusCode,
-	InvalidScaleEncoding,
-}
-
-impl From<scale::Error> for ExtensionError {
-	fn from(_: scale::Error) -> Self {
-		ExtensionError::InvalidScaleEncoding
-	}
-}
-
-impl ink_env::chain_extension::FromStatusCode for ExtensionError {
-	fn from_status_code(status_code: u32) -> Result<(), Self> {
-		match status_code {
-			0 => Ok(()),
-			1 => Err(Self::InvalidTokenId),
-			2 => Err(Self::InsufficientBalance),
-			403 => Err(Self::AccessDenied),
-			_ => Err(Self::UnknownStatusCode),
-		}
-	}
-}
-
-type Balance = <ink_env::DefaultEnvironment as Environment>::Balance;
-
-#[ink::chain_extension]
-pub trait NativeTokenRuntimeExt {
-	type ErrorCode = ExtensionError;
 
-	#[ink(extension = 10, returns_result = false)]
-	fn whitelist_contract();
+pub use self::native_token_wrapper::{Error, NativeTokenRef};
+// the extension moved to `laguna-env`, its old names stay for contracts built against the wrapper
+pub use laguna_env::{
+	ExtensionError, LagunaEnvironment as CustomEnvironment,
+	LagunaExtension as NativeTokenRuntimeExt,
+};
 
-	#[ink(extension = 200, returns_result = false)]
-	fn is_valid_token(token_id: u32);
+/// EIP-712 domain type of the permits.
+///
+/// Contract addresses don't fit the `address verifyingContract` of the standard domain, so the
+/// domain is bound to the wrapper through its `salt` instead. System wrappers live at the same
+/// address on every network, `chainId` keeps a permit from being replayed on another one.
+const DOMAIN_TYPEHASH: &[u8] =
+	b"EIP712Domain(string name,string version,uint256 chainId,bytes32 salt)";
 
-	#[ink(extension = 201)]
-	fn name(token_id: u32) -> Result<StorageVec<u8>, ExtensionError>;
+/// EIP-2612 permit type, as the interface exposes it: the owner is an ethereum address and the
+/// spender a substrate account, hence `bytes20` and `bytes32` rather than `address`
+const PERMIT_TYPEHASH: &[u8] =
+	b"Permit(bytes20 owner,bytes32 spender,uint256 value,uint256 nonce,uint256 deadline)";
 
-	#[ink(extension = 202)]
-	fn symbol(token_id: u32) -> Result<StorageVec<u8>, ExtensionError>;
+/// Storage key of the chain id permits are bound to, outside the storage struct for the same
+/// reason as `MAX_BATCH_SIZE_KEY`. Without the key, the wrapper doesn't accept permits.
+const CHAIN_ID_KEY: [u8; 32] = *b"native_token_wrapper::chain_id\0\0";
 
-	#[ink(extension = 203)]
-	fn decimals(token_id: u32) -> Result<u8, ExtensionError>;
+/// Selector of solidity's `Error(string)`, the revert reason solang callers can catch
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
 
-	#[ink(extension = 204)]
-	fn total_supply(token_id: u32) -> Result<Balance, ExtensionError>;
+/// Recipients a single `batch_transfer` accepts unless set otherwise at instantiation
+pub const DEFAULT_MAX_BATCH_SIZE: u32 = 64;
 
-	#[ink(extension = 205)]
-	fn balance_of(token_id: u32, owner: AccountId) -> Result<Balance, ExtensionError>;
+/// Storage key of the `batch_transfer` bound.
+///
+/// It lives outside the storage struct: ink! loads the whole struct on every message, so a
+/// wrapper instantiated before the bound existed would trap once upgraded in place. Without the
+/// key, `DEFAULT_MAX_BATCH_SIZE` applies.
+const MAX_BATCH_SIZE_KEY: [u8; 32] = *b"native_token_wrapper::batch_size";
 
-	#[ink(extension = 206, returns_result = false)]
-	fn transfer(token_id: u32, to: AccountId, value: Balance);
-
-	#[ink(extension = 207, returns_result = false)]
-	fn transfer_from(token_id: u32, from: AccountId, to: AccountId, value: Balance);
-}
-
-// Contract needs the environment that understand our extension
-#[derive(Debug, Clone, PartialEq, Eq)]
-#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
-pub enum CustomEnvironment {}
-
-impl Environment for CustomEnvironment {
-	const MAX_EVENT_TOPICS: usize = <ink_env::DefaultEnvironment as Environment>::MAX_EVENT_TOPICS;
-
-	type AccountId = <ink_env::DefaultEnvironment as Environment>::AccountId;
-	type Balance = <ink_env::DefaultEnvironment as Environment>::Balance;
-	type Hash = <ink_env::DefaultEnvironment as Environment>::Hash;
-	type BlockNumber = <ink_env::DefaultEnvironment as Environment>::BlockNumber;
-	type Timestamp = <ink_env::DefaultEnvironment as Environment>::Timestamp;
-
-	type ChainExtension = NativeTokenRuntimeExt;
-}
-
-pub use self::native_token_wrapper::NativeTokenRef;
-
-#[ink::contract(env = crate::CustomEnvironment)]
+#[ink::contract(env = laguna_env::LagunaEnvironment)]
 mod native_token_wrapper {
-	use super::StorageVec;
+	use super::{
+		ExtensionError, StorageVec, CHAIN_ID_KEY, DEFAULT_MAX_BATCH_SIZE, DOMAIN_TYPEHASH,
+		ERROR_SELECTOR, MAX_BATCH_SIZE_KEY, PERMIT_TYPEHASH,
+	};
 	use ethereum_types::U256;
+	use ink_env::{
+		hash::{Blake2x256, HashOutput, Keccak256},
+		ReturnFlags,
+	};
+	use ink_primitives::Key;
 	use ink_storage::{traits::SpreadAllocate, Mapping};
+	#[cfg(feature = "mint-burn")]
+	use laguna_env::supply;
 
 	#[ink(storage)]
 	#[derive(SpreadAllocate)]
@@ -100,6 +64,67 @@ mod native_token_wrapper {
 		/// Mapping of the token amount which an account is allowed to withdraw
 		/// from another account.
 		allowances: Mapping<(AccountId, AccountId), [u64; 4]>,
+		/// Number of permits used by each ethereum owner
+		nonces: Mapping<[u8; 20], u64>,
+	}
+
+	/// Reason a `try_*` message, `batch_transfer` or `permit` reverted with.
+	///
+	/// They revert with the `reason` of the error as solidity's `Error(string)`.
+	#[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+	#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+	pub enum Error {
+		/// The allowance of the caller doesn't cover the value, or would drop below zero
+		InsufficientAllowance,
+		/// The allowance would exceed `U256::MAX`
+		AllowanceOverflow,
+		/// The value doesn't fit a native balance
+		BalanceOverflow,
+		/// The runtime refused the operation
+		Extension(ExtensionError),
+		/// More recipients than `max_batch_size`
+		BatchTooLarge,
+		/// The wrapper was instantiated without a chain id, permits can't be bound to a domain
+		UnknownChain,
+		/// The deadline of the permit has passed
+		PermitExpired,
+		/// The permit isn't signed by its owner, or with another nonce
+		InvalidSignature,
+		/// The wrapper was built without the `mint-burn` feature
+		Unsupported,
+	}
+
+	impl From<ExtensionError> for Error {
+		fn from(e: ExtensionError) -> Self {
+			Error::Extension(e)
+		}
+	}
+
+	impl Error {
+		/// Reason reverted with, the name of the variant or of the runtime's error
+		pub fn reason(&self) -> &'static str {
+			match self {
+				Error::InsufficientAllowance => "InsufficientAllowance",
+				Error::AllowanceOverflow => "AllowanceOverflow",
+				Error::BalanceOverflow => "BalanceOverflow",
+				Error::BatchTooLarge => "BatchTooLarge",
+				Error::UnknownChain => "UnknownChain",
+				Error::PermitExpired => "PermitExpired",
+				Error::InvalidSignature => "InvalidSignature",
+				Error::Unsupported => "Unsupported",
+				Error::Extension(e) => match e {
+					ExtensionError::InvalidTokenId => "InvalidTokenId",
+					ExtensionError::InsufficientBalance => "InsufficientBalance",
+					ExtensionError::AccessDenied => "AccessDenied",
+					ExtensionError::UnknownStatusCode => "UnknownStatusCode",
+					ExtensionError::InvalidScaleEncoding => "InvalidScaleEncoding",
+					ExtensionError::BelowExistentialDeposit => "BelowExistentialDeposit",
+					ExtensionError::AccountFrozen => "AccountFrozen",
+					ExtensionError::ArithmeticOverflow => "ArithmeticOverflow",
+					ExtensionError::NotTransferable => "NotTransferable",
+				},
+			}
+		}
 	}
 
 	/// Event emitted when a token transfer occurs.
@@ -127,6 +152,28 @@ mod native_token_wrapper {
 		/// Creates an ERC-20 contract wrapper around an existing native token
 		#[ink(constructor, selector = 0x45fd0674)]
 		pub fn create_wrapper_token(token_id: u32) -> Self {
+			Self::create_wrapper_token_with_batch_size(token_id, DEFAULT_MAX_BATCH_SIZE)
+		}
+
+		/// Like `create_wrapper_token`, accepting up to `max_batch_size` recipients per
+		/// `batch_transfer`
+		#[ink(constructor, selector = 0xa71933b7)]
+		pub fn create_wrapper_token_with_batch_size(token_id: u32, max_batch_size: u32) -> Self {
+			Self::create(token_id, max_batch_size, None)
+		}
+
+		/// Like `create_wrapper_token_with_batch_size`, accepting permits signed for the EVM
+		/// chain `chain_id`, the one `eth_chainId` returns
+		#[ink(constructor, selector = 0x8938e491)]
+		pub fn create_wrapper_token_for_chain(
+			token_id: u32,
+			max_batch_size: u32,
+			chain_id: u64,
+		) -> Self {
+			Self::create(token_id, max_batch_size, Some(chain_id))
+		}
+
+		fn create(token_id: u32, max_batch_size: u32, chain_id: Option<u64>) -> Self {
 			// Checks if a native token with given token_id exists in the runtime
 			if Self::env().extension().is_valid_token(token_id).is_err() {
 				panic!("Invalid tokenId")
@@ -136,11 +183,17 @@ mod native_token_wrapper {
 			if Self::env().extension().whitelist_contract().is_err() {
 				panic!("Failed to whitelist the contract")
 			}
-			ink_lang::utils::initialize_contract(|contract| Self::new_init(contract, token_id))
+			ink_lang::utils::initialize_contract(|contract| {
+				Self::new_init(contract, token_id, max_batch_size, chain_id)
+			})
 		}
 
-		fn new_init(&mut self, token_id: u32) {
-			self.token_id = token_id
+		fn new_init(&mut self, token_id: u32, max_batch_size: u32, chain_id: Option<u64>) {
+			self.token_id = token_id;
+			ink_env::set_contract_storage(&Key::from(MAX_BATCH_SIZE_KEY), &max_batch_size);
+			if let Some(chain_id) = chain_id {
+				ink_env::set_contract_storage(&Key::from(CHAIN_ID_KEY), &chain_id);
+			}
 		}
 
 		/// Returns the name of the token
@@ -205,19 +258,70 @@ mod native_token_wrapper {
 		/// On success a `Transfer` event is emitted.
 		#[ink(message, selector = 0xa9059cbb)]
 		pub fn transfer(&mut self, to: AccountId, value: U256) -> bool {
-			let val = match u128::try_from(value) {
-				Ok(val) => val,
-				Err(_) => return false,
-			};
-			if self.env().extension().transfer(self.token_id, to, val).is_err() {
-				return false
+			self.transfer_impl(to, value).is_ok()
+		}
+
+		/// Like `transfer`, but reverts with the reason of the failure as `Error(string)`.
+		#[ink(message, selector = 0x010a7d65)]
+		pub fn try_transfer(&mut self, to: AccountId, value: U256) {
+			if let Err(e) = self.transfer_impl(to, value) {
+				revert(e)
 			}
+		}
+
+		fn transfer_impl(&mut self, to: AccountId, value: U256) -> Result<(), Error> {
+			let val = to_balance(value)?;
+			self.env().extension().transfer(self.token_id, to, val)?;
 			self.env().emit_event(Transfer {
 				from: Some(self.env().caller()),
 				to: Some(to),
 				value,
 			});
-			true
+			Ok(())
+		}
+
+		/// Returns the maximum number of recipients of a `batch_transfer`, keeping it within the
+		/// block weight
+		#[ink(message, selector = 0x26fe8a46)]
+		pub fn max_batch_size(&self) -> u32 {
+			ink_env::get_contract_storage(&Key::from(MAX_BATCH_SIZE_KEY))
+				.ok()
+				.flatten()
+				.unwrap_or(DEFAULT_MAX_BATCH_SIZE)
+		}
+
+		/// Transfers to every `(to, value)` of `recipients` from the caller's account, either all
+		/// of them or none.
+		///
+		/// Reverts with `BatchTooLarge` beyond `max_batch_size` recipients, or with the reason of
+		/// the first failed transfer, as `Error(string)`. A `Transfer` event is emitted per
+		/// recipient.
+		#[ink(message, selector = 0xed5acb9c)]
+		pub fn batch_transfer(&mut self, recipients: StorageVec<(AccountId, U256)>) {
+			if let Err(e) = self.batch_transfer_impl(recipients) {
+				revert(e)
+			}
+		}
+
+		fn batch_transfer_impl(
+			&mut self,
+			recipients: StorageVec<(AccountId, U256)>,
+		) -> Result<(), Error> {
+			if recipients.len() > self.max_batch_size() as usize {
+				return Err(Error::BatchTooLarge)
+			}
+			// nothing moves unless every value is representable
+			let balances = recipients
+				.iter()
+				.map(|(_, value)| to_balance(*value))
+				.collect::<Result<StorageVec<_>, _>>()?;
+
+			let from = self.env().caller();
+			for ((to, value), val) in recipients.into_iter().zip(balances) {
+				self.env().extension().transfer(self.token_id, to, val)?;
+				self.env().emit_event(Transfer { from: Some(from), to: Some(to), value });
+			}
+			Ok(())
 		}
 
 		/// Allows `spender` to withdraw from the caller's account multiple times, up to
@@ -229,31 +333,1084 @@ mod native_token_wrapper {
 		#[ink(message, selector = 0x095ea7b3)]
 		pub fn approve(&mut self, spender: AccountId, value: U256) -> bool {
 			let owner = self.env().caller();
-			self.allowances.insert((&owner, &spender), &value.0);
-			self.env().emit_event(Approval { owner, spender, value });
-			true
+			self.set_allowance(owner, spender, value)
+		}
+
+		/// Atomically increases the allowance granted to `spender` by the caller.
+		///
+		/// Returns `false` if the allowance would overflow. An `Approval` event is emitted.
+		#[ink(message, selector = 0x39509351)]
+		pub fn increase_allowance(&mut self, spender: AccountId, added_value: U256) -> bool {
+			self.increase_allowance_impl(spender, added_value).is_ok()
+		}
+
+		/// Like `increase_allowance`, but reverts with the reason of the failure as
+		/// `Error(string)`.
+		#[ink(message, selector = 0x06e87a6a)]
+		pub fn try_increase_allowance(&mut self, spender: AccountId, added_value: U256) {
+			if let Err(e) = self.increase_allowance_impl(spender, added_value) {
+				revert(e)
+			}
+		}
+
+		fn increase_allowance_impl(
+			&mut self,
+			spender: AccountId,
+			added_value: U256,
+		) -> Result<(), Error> {
+			let owner = self.env().caller();
+			let value = self
+				.allowance(owner, spender)
+				.checked_add(added_value)
+				.ok_or(Error::AllowanceOverflow)?;
+			self.set_allowance(owner, spender, value);
+			Ok(())
+		}
+
+		/// Atomically decreases the allowance granted to `spender` by the caller.
+		///
+		/// Returns `false` if the allowance would drop below zero. An `Approval` event is emitted.
+		#[ink(message, selector = 0xa457c2d7)]
+		pub fn decrease_allowance(&mut self, spender: AccountId, subtracted_value: U256) -> bool {
+			self.decrease_allowance_impl(spender, subtracted_value).is_ok()
+		}
+
+		/// Like `decrease_allowance`, but reverts with the reason of the failure as
+		/// `Error(string)`.
+		#[ink(message, selector = 0xca86d613)]
+		pub fn try_decrease_allowance(&mut self, spender: AccountId, subtracted_value: U256) {
+			if let Err(e) = self.decrease_allowance_impl(spender, subtracted_value) {
+				revert(e)
+			}
+		}
+
+		fn decrease_allowance_impl(
+			&mut self,
+			spender: AccountId,
+			subtracted_value: U256,
+		) -> Result<(), Error> {
+			let owner = self.env().caller();
+			let value = self
+				.allowance(owner, spender)
+				.checked_sub(subtracted_value)
+				.ok_or(Error::InsufficientAllowance)?;
+			self.set_allowance(owner, spender, value);
+			Ok(())
 		}
 
 		/// Transfers `value` tokens on the behalf of `from` to the account `to`.
 		///
+		/// An allowance of `U256::MAX` is infinite and never decreases.
+		///
 		/// On success a `Transfer` event is emitted.
 		#[ink(message, selector = 0x23b872dd)]
 		pub fn transfer_from(&mut self, from: AccountId, to: AccountId, value: U256) -> bool {
+			self.transfer_from_impl(from, to, value).is_ok()
+		}
+
+		/// Like `transfer_from`, but reverts with the reason of the failure as `Error(string)`.
+		#[ink(message, selector = 0x0268df0f)]
+		pub fn try_transfer_from(&mut self, from: AccountId, to: AccountId, value: U256) {
+			if let Err(e) = self.transfer_from_impl(from, to, value) {
+				revert(e)
+			}
+		}
+
+		fn transfer_from_impl(
+			&mut self,
+			from: AccountId,
+			to: AccountId,
+			value: U256,
+		) -> Result<(), Error> {
 			let caller = self.env().caller();
 			let allowance = self.allowance(from, caller);
 			if allowance < value {
-				return false
+				return Err(Error::InsufficientAllowance)
 			}
-			let val = match u128::try_from(value) {
-				Ok(val) => val,
-				Err(_) => return false,
-			};
-			if self.env().extension().transfer_from(self.token_id, from, to, val).is_err() {
-				return false
+			let val = to_balance(value)?;
+			self.env().extension().transfer_from(self.token_id, from, to, val)?;
+			if allowance != U256::MAX {
+				self.allowances.insert((&from, &caller), &(allowance - value).0);
 			}
-			self.allowances.insert((&from, &caller), &(allowance - value).0);
 			self.env().emit_event(Transfer { from: Some(from), to: Some(to), value });
+			Ok(())
+		}
+
+		/// Creates `value` tokens and assigns them to `to`, increasing the total supply.
+		///
+		/// The runtime only lets callers holding the minter role of the token through. Built
+		/// without the `mint-burn` feature, it always fails with `Unsupported`.
+		///
+		/// On success a `Transfer` event from `None` is emitted.
+		#[ink(message, selector = 0x40c10f19)]
+		pub fn mint(&mut self, to: AccountId, value: U256) -> bool {
+			self.mint_impl(to, value).is_ok()
+		}
+
+		/// Like `mint`, but reverts with the reason of the failure as `Error(string)`.
+		#[ink(message, selector = 0xb49225d3)]
+		pub fn try_mint(&mut self, to: AccountId, value: U256) {
+			if let Err(e) = self.mint_impl(to, value) {
+				revert(e)
+			}
+		}
+
+		fn mint_impl(&mut self, to: AccountId, value: U256) -> Result<(), Error> {
+			let val = to_balance(value)?;
+			let origin = self.env().caller();
+			supply::mint(self.token_id, origin, to, val)?;
+			self.env().emit_event(Transfer { from: None, to: Some(to), value });
+			Ok(())
+		}
+
+		/// Destroys `value` tokens from the account `from`, decreasing the total supply.
+		///
+		/// The runtime only lets callers holding the burner role of the token through. Built
+		/// without the `mint-burn` feature, it always fails with `Unsupported`.
+		///
+		/// On success a `Transfer` event to `None` is emitted.
+		#[ink(message, selector = 0x9dc29fac)]
+		pub fn burn(&mut self, from: AccountId, value: U256) -> bool {
+			self.burn_impl(from, value).is_ok()
+		}
+
+		/// Like `burn`, but reverts with the reason of the failure as `Error(string)`.
+		#[ink(message, selector = 0x7da945d1)]
+		pub fn try_burn(&mut self, from: AccountId, value: U256) {
+			if let Err(e) = self.burn_impl(from, value) {
+				revert(e)
+			}
+		}
+
+		fn burn_impl(&mut self, from: AccountId, value: U256) -> Result<(), Error> {
+			let val = to_balance(value)?;
+			let origin = self.env().caller();
+			supply::burn(self.token_id, origin, from, val)?;
+			self.env().emit_event(Transfer { from: Some(from), to: None, value });
+			Ok(())
+		}
+
+		fn set_allowance(&mut self, owner: AccountId, spender: AccountId, value: U256) -> bool {
+			self.allowances.insert((&owner, &spender), &value.0);
+			self.env().emit_event(Approval { owner, spender, value });
 			true
 		}
+
+		/// Returns the EIP-712 domain separator permits are signed against.
+		///
+		/// Reverts with `UnknownChain` as `Error(string)` unless the wrapper was instantiated
+		/// with a chain id.
+		#[ink(message, selector = 0x3644e515)]
+		pub fn domain_separator(&self) -> [u8; 32] {
+			match self.domain_separator_impl() {
+				Ok(separator) => separator,
+				Err(e) => revert(e),
+			}
+		}
+
+		fn domain_separator_impl(&self) -> Result<[u8; 32], Error> {
+			let chain_id: u64 = ink_env::get_contract_storage(&Key::from(CHAIN_ID_KEY))
+				.ok()
+				.flatten()
+				.ok_or(Error::UnknownChain)?;
+			let mut chain_word = [0u8; 32];
+			U256::from(chain_id).to_big_endian(&mut chain_word);
+
+			let mut encoded = StorageVec::with_capacity(5 * 32);
+			encoded.extend_from_slice(&keccak(DOMAIN_TYPEHASH));
+			encoded.extend_from_slice(&keccak(&self.name()));
+			encoded.extend_from_slice(&keccak(b"1"));
+			encoded.extend_from_slice(&chain_word);
+			encoded.extend_from_slice(AsRef::<[u8; 32]>::as_ref(&self.env().account_id()));
+			Ok(keccak(&encoded))
+		}
+
+		/// Returns the nonce the next permit of `owner` has to be signed with
+		#[ink(message, selector = 0x2c992c08)]
+		pub fn nonces(&self, owner: [u8; 20]) -> U256 {
+			U256::from(self.nonces.get(owner).unwrap_or_default())
+		}
+
+		/// Sets the allowance of `spender` over the tokens of the default account of the ethereum
+		/// address `owner`, given an EIP-712 signature of `owner` (EIP-2612).
+		///
+		/// The default account is the one the runtime maps `owner` to unless a proxy account was
+		/// registered for it, tokens of proxy accounts can't be permitted.
+		///
+		/// `deadline` is a unix timestamp in seconds. Reverts with `PermitExpired`,
+		/// `InvalidSignature` or `UnknownChain` as `Error(string)`, else an `Approval` event is
+		/// emitted.
+		#[ink(message, selector = 0x532681b0)]
+		#[allow(clippy::too_many_arguments)]
+		pub fn permit(
+			&mut self,
+			owner: [u8; 20],
+			spender: AccountId,
+			value: U256,
+			deadline: U256,
+			v: u8,
+			r: [u8; 32],
+			s: [u8; 32],
+		) {
+			if let Err(e) = self.permit_impl(owner, spender, value, deadline, v, r, s) {
+				revert(e)
+			}
+		}
+
+		#[allow(clippy::too_many_arguments)]
+		fn permit_impl(
+			&mut self,
+			owner: [u8; 20],
+			spender: AccountId,
+			value: U256,
+			deadline: U256,
+			v: u8,
+			r: [u8; 32],
+			s: [u8; 32],
+		) -> Result<(), Error> {
+			if deadline < U256::from(self.env().block_timestamp() / 1000) {
+				return Err(Error::PermitExpired)
+			}
+
+			let nonce = self.nonces.get(owner).unwrap_or_default();
+
+			// `bytes20` is padded on the right, unlike `address`
+			let mut encoded = StorageVec::with_capacity(6 * 32);
+			encoded.extend_from_slice(&keccak(PERMIT_TYPEHASH));
+			encoded.extend_from_slice(&owner);
+			encoded.extend_from_slice(&[0u8; 12]);
+			encoded.extend_from_slice(AsRef::<[u8; 32]>::as_ref(&spender));
+			for word in [value, U256::from(nonce), deadline] {
+				let mut buf = [0u8; 32];
+				word.to_big_endian(&mut buf);
+				encoded.extend_from_slice(&buf);
+			}
+
+			let mut digest = StorageVec::with_capacity(2 + 2 * 32);
+			digest.extend_from_slice(b"\x19\x01");
+			digest.extend_from_slice(&self.domain_separator_impl()?);
+			digest.extend_from_slice(&keccak(&encoded));
+
+			let mut signature = [0u8; 65];
+			signature[..32].copy_from_slice(&r);
+			signature[32..64].copy_from_slice(&s);
+			signature[64] = if v >= 27 { v - 27 } else { v };
+
+			if recover_eth_address(signature, keccak(&digest)) != Some(owner) {
+				return Err(Error::InvalidSignature)
+			}
+
+			self.nonces.insert(owner, &(nonce + 1));
+			self.set_allowance(default_account(owner), spender, value);
+			Ok(())
+		}
+	}
+
+	/// Stands in for `laguna_env::supply` on runtimes without extensions 210 and 211
+	#[cfg(not(feature = "mint-burn"))]
+	mod supply {
+		use super::{AccountId, Balance, Error};
+
+		pub fn mint(_: u32, _: AccountId, _: AccountId, _: Balance) -> Result<(), Error> {
+			Err(Error::Unsupported)
+		}
+
+		pub fn burn(_: u32, _: AccountId, _: AccountId, _: Balance) -> Result<(), Error> {
+			Err(Error::Unsupported)
+		}
+	}
+
+	/// Native balances are `u128`, larger values can never be moved
+	fn to_balance(value: U256) -> Result<Balance, Error> {
+		u128::try_from(value).map_err(|_| Error::BalanceOverflow)
+	}
+
+	/// Ends the call the way solidity's `revert(reason)` does: the selector of `Error(string)`
+	/// followed by the reason, which solang callers decode with `catch Error(string memory)`
+	fn revert(error: Error) -> ! {
+		ink_env::return_value::<([u8; 4], &str)>(
+			ReturnFlags::default().set_reverted(true),
+			&(ERROR_SELECTOR, error.reason()),
+		)
+	}
+
+	fn keccak(input: &[u8]) -> [u8; 32] {
+		let mut output = <Keccak256 as HashOutput>::Type::default();
+		ink_env::hash_bytes::<Keccak256>(input, &mut output);
+		output
+	}
+
+	/// Account the runtime maps `address` to without a proxy account: blake2_256("evm:" ++ address)
+	fn default_account(address: [u8; 20]) -> AccountId {
+		let mut input = StorageVec::with_capacity(4 + 20);
+		input.extend_from_slice(b"evm:");
+		input.extend_from_slice(&address);
+		let mut output = <Blake2x256 as HashOutput>::Type::default();
+		ink_env::hash_bytes::<Blake2x256>(&input, &mut output);
+		output.into()
+	}
+
+	/// Recovers the ethereum address which signed `message_hash` through the `ecdsa_recover` and
+	/// `ecdsa_to_eth_address` messages of `env_utils`
+	#[cfg(not(test))]
+	fn recover_eth_address(signature: [u8; 65], message_hash: [u8; 32]) -> Option<[u8; 20]> {
+		use ink_env::call::{build_call, Call, ExecutionInput, Selector};
+		use laguna_env::ENV_UTILS;
+
+		let env_utils = AccountId::from(ENV_UTILS);
+
+		let (ok, pubkey) = build_call::<laguna_env::LagunaEnvironment>()
+			.call_type(Call::new().callee(env_utils))
+			.exec_input(
+				ExecutionInput::new(Selector::new([0xe1, 0xe0, 0xe8, 0x95]))
+					.push_arg(signature)
+					.push_arg(message_hash),
+			)
+			.returns::<(bool, [u8; 33])>()
+			.fire()
+			.ok()?;
+		if !ok {
+			return None
+		}
+
+		let (ok, address) = build_call::<laguna_env::LagunaEnvironment>()
+			.call_type(Call::new().callee(env_utils))
+			.exec_input(
+				ExecutionInput::new(Selector::new([0x71, 0x43, 0xb5, 0x98])).push_arg(pubkey),
+			)
+			.returns::<(bool, [u8; 20])>()
+			.fire()
+			.ok()?;
+
+		ok.then(|| address)
+	}
+
+	/// Contracts can't be called off-chain, the facilities `env_utils` wraps are used directly
+	#[cfg(test)]
+	fn recover_eth_address(signature: [u8; 65], message_hash: [u8; 32]) -> Option<[u8; 20]> {
+		let mut pubkey = [0u8; 33];
+		ink_env::ecdsa_recover(&signature, &message_hash, &mut pubkey).ok()?;
+		let mut address = [0u8; 20];
+		ink_env::ecdsa_to_eth_address(&pubkey, &mut address).ok()?;
+		Some(address)
+	}
+
+	#[cfg(test)]
+	mod tests {
+		use super::*;
+		use ink_lang as ink;
+		use ink_prelude::collections::{BTreeMap, BTreeSet};
+		use scale::{Decode, Encode};
+		use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
+		use std::{cell::RefCell, rc::Rc};
+
+		type Event = <NativeToken as ::ink_lang::reflect::ContractEventBase>::Type;
+
+		const TOKEN_ID: u32 = 0;
+		const CHAIN_ID: u64 = 1000;
+
+		/// In-memory stand-in for the native token pallets behind `LagunaExtension`
+		#[derive(Default)]
+		struct Ledger {
+			/// Account the wrapper is called by, the runtime sees it as the origin of transfers
+			caller: AccountId,
+			balances: BTreeMap<AccountId, Balance>,
+			minters: BTreeSet<AccountId>,
+			/// Status code returned by an extension instead of running it
+			fail_with: BTreeMap<u32, u32>,
+			/// Extensions whose output is garbage
+			corrupt: BTreeSet<u32>,
+		}
+
+		impl Ledger {
+			fn total_supply(&self) -> Balance {
+				self.balances.values().sum()
+			}
+
+			fn move_balance(&mut self, from: AccountId, to: AccountId, value: Balance) -> u32 {
+				let from_balance = self.balances.get(&from).copied().unwrap_or_default();
+				if from_balance < value {
+					return 2
+				}
+				self.balances.insert(from, from_balance - value);
+				*self.balances.entry(to).or_default() += value;
+				0
+			}
+		}
+
+		struct MockedExtension {
+			func_id: u32,
+			ledger: Rc<RefCell<Ledger>>,
+		}
+
+		impl ink_env::test::ChainExtension for MockedExtension {
+			fn func_id(&self) -> u32 {
+				self.func_id
+			}
+
+			fn call(&mut self, mut input: &[u8], output: &mut Vec<u8>) -> u32 {
+				let mut ledger = self.ledger.borrow_mut();
+
+				if let Some(status) = ledger.fail_with.get(&self.func_id) {
+					return *status
+				}
+				if ledger.corrupt.contains(&self.func_id) {
+					output.push(0xff);
+					return 0
+				}
+
+				fn ok<T: Encode>(value: T, output: &mut Vec<u8>) -> u32 {
+					Ok::<T, ExtensionError>(value).encode_to(output);
+					0
+				}
+
+				let token_id = u32::decode(&mut input).unwrap_or_default();
+				if self.func_id != 10 && token_id != TOKEN_ID {
+					return 1
+				}
+
+				match self.func_id {
+					10 | 200 => 0,
+					201 | 202 => ok(b"LAGUNA".to_vec(), output),
+					203 => ok(18_u8, output),
+					204 => ok(ledger.total_supply(), output),
+					205 => {
+						let owner = AccountId::decode(&mut input).unwrap();
+						ok(ledger.balances.get(&owner).copied().unwrap_or_default(), output)
+					},
+					206 => {
+						let (to, value) = <(AccountId, Balance)>::decode(&mut input).unwrap();
+						let from = ledger.caller;
+						ledger.move_balance(from, to, value)
+					},
+					207 => {
+						let (from, to, value) =
+							<(AccountId, AccountId, Balance)>::decode(&mut input).unwrap();
+						ledger.move_balance(from, to, value)
+					},
+					210 | 211 => {
+						let (origin, account, value) =
+							<(AccountId, AccountId, Balance)>::decode(&mut input).unwrap();
+						if !ledger.minters.contains(&origin) {
+							return 403
+						}
+						let balance = ledger.balances.entry(account).or_default();
+						if self.func_id == 210 {
+							*balance += value;
+						} else if *balance < value {
+							return 2
+						} else {
+							*balance -= value;
+						}
+						0
+					},
+					_ => unreachable!("extension {} isn't mocked", self.func_id),
+				}
+			}
+		}
+
+		fn accounts() -> ink_env::test::DefaultAccounts<ink_env::DefaultEnvironment> {
+			ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+		}
+
+		/// Registers the mocked extensions, ALICE holding 1000 tokens and the minter role
+		fn setup() -> (NativeToken, Rc<RefCell<Ledger>>) {
+			let ledger = Rc::new(RefCell::new(Ledger::default()));
+			{
+				let mut ledger = ledger.borrow_mut();
+				ledger.balances.insert(accounts().alice, 1000);
+				ledger.minters.insert(accounts().alice);
+			}
+
+			for func_id in [10, 200, 201, 202, 203, 204, 205, 206, 207, 210, 211] {
+				ink_env::test::register_chain_extension(MockedExtension {
+					func_id,
+					ledger: ledger.clone(),
+				});
+			}
+
+			set_caller(&ledger, accounts().alice);
+			(NativeToken::create_wrapper_token(TOKEN_ID), ledger)
+		}
+
+		fn set_caller(ledger: &Rc<RefCell<Ledger>>, caller: AccountId) {
+			ledger.borrow_mut().caller = caller;
+			ink_env::test::set_caller::<ink_env::DefaultEnvironment>(caller);
+		}
+
+		/// Wrapper accepting permits of `CHAIN_ID`, to be created after `setup`
+		fn permit_wrapper() -> NativeToken {
+			NativeToken::create_wrapper_token_for_chain(TOKEN_ID, DEFAULT_MAX_BATCH_SIZE, CHAIN_ID)
+		}
+
+		fn signer(seed: u8) -> SecretKey {
+			SecretKey::from_slice(&[seed; 32]).unwrap()
+		}
+
+		fn eth_address(key: &SecretKey) -> [u8; 20] {
+			let pubkey = PublicKey::from_secret_key(&Secp256k1::new(), key).serialize();
+			let mut address = [0u8; 20];
+			ink_env::ecdsa_to_eth_address(&pubkey, &mut address).unwrap();
+			address
+		}
+
+		/// Signs a permit the way `eth_signTypedData` does, returning `(v, r, s)`
+		fn sign_permit(
+			domain_separator: [u8; 32],
+			key: &SecretKey,
+			spender: AccountId,
+			value: U256,
+			nonce: U256,
+			deadline: U256,
+		) -> (u8, [u8; 32], [u8; 32]) {
+			let word = |v: U256| {
+				let mut buf = [0u8; 32];
+				v.to_big_endian(&mut buf);
+				buf
+			};
+			let struct_hash = keccak(
+				&[
+					&keccak(PERMIT_TYPEHASH)[..],
+					&eth_address(key),
+					&[0u8; 12],
+					AsRef::<[u8; 32]>::as_ref(&spender),
+					&word(value),
+					&word(nonce),
+					&word(deadline),
+				]
+				.concat(),
+			);
+			let digest = keccak(&[&b"\x19\x01"[..], &domain_separator, &struct_hash].concat());
+
+			let signature = Secp256k1::new()
+				.sign_ecdsa_recoverable(&Message::from_slice(&digest).unwrap(), key);
+			let (recovery_id, rs) = signature.serialize_compact();
+			(
+				recovery_id.to_i32() as u8 + 27,
+				rs[..32].try_into().unwrap(),
+				rs[32..].try_into().unwrap(),
+			)
+		}
+
+		fn events() -> Vec<Event> {
+			ink_env::test::recorded_events()
+				.map(|e| <Event as Decode>::decode(&mut &e.data[..]).unwrap())
+				.collect()
+		}
+
+		fn assert_transfer(
+			event: &Event,
+			from: Option<AccountId>,
+			to: Option<AccountId>,
+			value: u128,
+		) {
+			match event {
+				Event::Transfer(t) => {
+					assert_eq!((t.from, t.to, t.value), (from, to, U256::from(value)))
+				},
+				_ => panic!("expected a Transfer event"),
+			}
+		}
+
+		fn assert_approval(event: &Event, owner: AccountId, spender: AccountId, value: U256) {
+			match event {
+				Event::Approval(a) => {
+					assert_eq!((a.owner, a.spender, a.value), (owner, spender, value))
+				},
+				_ => panic!("expected an Approval event"),
+			}
+		}
+
+		#[ink::test]
+		fn metadata_comes_from_the_runtime() {
+			let (token, _) = setup();
+			assert_eq!(token.name(), b"LAGUNA".to_vec());
+			assert_eq!(token.symbol(), b"LAGUNA".to_vec());
+			assert_eq!(token.decimals(), 18);
+			assert_eq!(token.total_supply(), U256::from(1000));
+			assert_eq!(token.balance_of(accounts().alice), U256::from(1000));
+			assert_eq!(token.balance_of(accounts().bob), U256::zero());
+		}
+
+		#[ink::test]
+		fn domain_separator_binds_the_chain() {
+			setup();
+			let token = permit_wrapper();
+			let separator = token.domain_separator_impl().unwrap();
+			assert_eq!(token.domain_separator_impl(), Ok(separator));
+
+			let token = NativeToken::create_wrapper_token_for_chain(
+				TOKEN_ID,
+				DEFAULT_MAX_BATCH_SIZE,
+				CHAIN_ID + 1,
+			);
+			assert_ne!(token.domain_separator_impl(), Ok(separator));
+		}
+
+		#[test]
+		fn owners_map_to_their_default_account() {
+			// blake2_256("evm:" ++ 0x1111..11), as laguna's EvmCompat derives it
+			assert_eq!(
+				AsRef::<[u8; 32]>::as_ref(&default_account([0x11; 20])),
+				&[
+					0x32, 0xf9, 0x55, 0xc9, 0x58, 0xe5, 0x11, 0x89, 0xa4, 0x92, 0x1a, 0xed, 0x41,
+					0xef, 0x00, 0x81, 0x8f, 0x73, 0x68, 0xdf, 0xae, 0xc8, 0xd9, 0x96, 0x9f, 0x09,
+					0x10, 0x06, 0xf8, 0x06, 0x62, 0x28
+				]
+			);
+		}
+
+		#[ink::test]
+		fn permits_need_a_chain_id() {
+			let (mut token, _) = setup();
+			let key = signer(1);
+			let (owner, bob) = (eth_address(&key), accounts().bob);
+			let (value, deadline) = (U256::from(10), U256::from(u64::MAX));
+
+			assert_eq!(token.domain_separator_impl(), Err(Error::UnknownChain));
+			let (v, r, s) = sign_permit([0u8; 32], &key, bob, value, U256::zero(), deadline);
+			assert_eq!(
+				token.permit_impl(owner, bob, value, deadline, v, r, s),
+				Err(Error::UnknownChain)
+			);
+		}
+
+		#[ink::test]
+		fn permit_sets_the_allowance_of_the_owner() {
+			setup();
+			let mut token = permit_wrapper();
+			let key = signer(1);
+			let (owner, bob) = (eth_address(&key), accounts().bob);
+			let (value, deadline) = (U256::from(10), U256::from(u64::MAX));
+
+			assert_eq!(token.nonces(owner), U256::zero());
+			let separator = token.domain_separator_impl().unwrap();
+			let (v, r, s) = sign_permit(separator, &key, bob, value, U256::zero(), deadline);
+			assert_eq!(token.permit_impl(owner, bob, value, deadline, v, r, s), Ok(()));
+
+			let owner_account = default_account(owner);
+			assert_eq!(token.allowance(owner_account, bob), value);
+			assert_eq!(token.nonces(owner), U256::one());
+
+			let events = events();
+			assert_eq!(events.len(), 1);
+			assert_approval(&events[0], owner_account, bob, value);
+		}
+
+		#[ink::test]
+		fn permit_cannot_be_replayed() {
+			setup();
+			let mut token = permit_wrapper();
+			let key = signer(1);
+			let (owner, bob) = (eth_address(&key), accounts().bob);
+			let (value, deadline) = (U256::from(10), U256::from(u64::MAX));
+
+			let separator = token.domain_separator_impl().unwrap();
+			let (v, r, s) = sign_permit(separator, &key, bob, value, U256::zero(), deadline);
+			assert_eq!(token.permit_impl(owner, bob, value, deadline, v, r, s), Ok(()));
+
+			// the signature is bound to the nonce which was just used up
+			assert_eq!(
+				token.permit_impl(owner, bob, value, deadline, v, r, s),
+				Err(Error::InvalidSignature)
+			);
+			assert_eq!(token.nonces(owner), U256::one());
+		}
+
+		#[ink::test]
+		fn expired_permit_is_rejected() {
+			setup();
+			let mut token = permit_wrapper();
+			let key = signer(1);
+			let (owner, bob) = (eth_address(&key), accounts().bob);
+			let (value, deadline) = (U256::from(10), U256::from(1000));
+
+			// milliseconds, one second past the deadline
+			ink_env::test::set_block_timestamp::<ink_env::DefaultEnvironment>(1_001_000);
+			let separator = token.domain_separator_impl().unwrap();
+			let (v, r, s) = sign_permit(separator, &key, bob, value, U256::zero(), deadline);
+			assert_eq!(
+				token.permit_impl(owner, bob, value, deadline, v, r, s),
+				Err(Error::PermitExpired)
+			);
+			assert_eq!(token.nonces(owner), U256::zero());
+			assert!(events().is_empty());
+		}
+
+		#[ink::test]
+		fn permit_of_another_signer_is_rejected() {
+			setup();
+			let mut token = permit_wrapper();
+			let (owner, bob) = (eth_address(&signer(1)), accounts().bob);
+			let (value, deadline) = (U256::from(10), U256::from(u64::MAX));
+
+			let separator = token.domain_separator_impl().unwrap();
+			let (v, r, s) = sign_permit(separator, &signer(2), bob, value, U256::zero(), deadline);
+			assert_eq!(
+				token.permit_impl(owner, bob, value, deadline, v, r, s),
+				Err(Error::InvalidSignature)
+			);
+			assert_eq!(token.allowance(default_account(owner), bob), U256::zero());
+			assert_eq!(token.nonces(owner), U256::zero());
+		}
+
+		#[ink::test]
+		#[should_panic(expected = "Invalid tokenId")]
+		fn unknown_token_cannot_be_wrapped() {
+			setup();
+			NativeToken::create_wrapper_token(TOKEN_ID + 1);
+		}
+
+		#[ink::test]
+		#[should_panic(expected = "Failed to whitelist the contract")]
+		fn wrapping_requires_the_whitelist() {
+			let (_, ledger) = setup();
+			ledger.borrow_mut().fail_with.insert(10, 403);
+			NativeToken::create_wrapper_token(TOKEN_ID);
+		}
+
+		#[ink::test]
+		#[should_panic(expected = "TokenId once created is never destroyed")]
+		fn undecodable_output_is_fatal() {
+			let (token, ledger) = setup();
+			ledger.borrow_mut().corrupt.insert(203);
+			token.decimals();
+		}
+
+		#[ink::test]
+		fn transfer_works() {
+			let (mut token, ledger) = setup();
+
+			assert!(token.transfer(accounts().bob, U256::from(10)));
+			assert_eq!(token.balance_of(accounts().alice), U256::from(990));
+			assert_eq!(token.balance_of(accounts().bob), U256::from(10));
+
+			let events = events();
+			assert_eq!(events.len(), 1);
+			assert_transfer(&events[0], Some(accounts().alice), Some(accounts().bob), 10);
+
+			// the caller seen by the runtime pays
+			set_caller(&ledger, accounts().bob);
+			assert!(token.transfer(accounts().charlie, U256::from(4)));
+			assert_eq!(token.balance_of(accounts().bob), U256::from(6));
+		}
+
+		#[ink::test]
+		fn transfer_fails_without_side_effects() {
+			let (mut token, ledger) = setup();
+
+			// InsufficientBalance
+			assert!(!token.transfer(accounts().bob, U256::from(1001)));
+
+			// values beyond u128 never reach the runtime
+			assert!(!token.transfer(accounts().bob, U256::from(u128::MAX) + 1));
+			assert!(!token.transfer(accounts().bob, U256::MAX));
+
+			// InvalidTokenId, AccessDenied and unknown codes
+			for status in [1, 403, 7] {
+				ledger.borrow_mut().fail_with.insert(206, status);
+				assert!(!token.transfer(accounts().bob, U256::from(1)));
+			}
+
+			assert_eq!(token.balance_of(accounts().alice), U256::from(1000));
+			assert!(events().is_empty());
+		}
+
+		#[ink::test]
+		fn try_variants_report_the_reason() {
+			let (mut token, ledger) = setup();
+			let (alice, bob) = (accounts().alice, accounts().bob);
+
+			assert_eq!(
+				token.transfer_impl(bob, U256::from(1001)),
+				Err(Error::Extension(ExtensionError::InsufficientBalance))
+			);
+			assert_eq!(token.transfer_impl(bob, U256::MAX), Err(Error::BalanceOverflow));
+
+			for (status, error) in [
+				(1, ExtensionError::InvalidTokenId),
+				(3, ExtensionError::BelowExistentialDeposit),
+				(4, ExtensionError::AccountFrozen),
+				(5, ExtensionError::ArithmeticOverflow),
+				(6, ExtensionError::NotTransferable),
+				(7, ExtensionError::UnknownStatusCode),
+			] {
+				ledger.borrow_mut().fail_with.insert(206, status);
+				assert_eq!(token.transfer_impl(bob, U256::one()), Err(Error::Extension(error)));
+			}
+
+			assert!(token.approve(bob, U256::from(10)));
+			assert_eq!(
+				token.decrease_allowance_impl(bob, U256::from(11)),
+				Err(Error::InsufficientAllowance)
+			);
+			assert_eq!(
+				token.increase_allowance_impl(bob, U256::MAX),
+				Err(Error::AllowanceOverflow)
+			);
+
+			set_caller(&ledger, bob);
+			assert_eq!(
+				token.transfer_from_impl(alice, bob, U256::from(11)),
+				Err(Error::InsufficientAllowance)
+			);
+
+			// the successful path is the same as the plain messages'
+			set_caller(&ledger, alice);
+			ledger.borrow_mut().fail_with.clear();
+			assert_eq!(token.transfer_impl(bob, U256::from(10)), Ok(()));
+			assert_eq!(token.balance_of(bob), U256::from(10));
+		}
+
+		#[test]
+		fn reasons_encode_as_solidity_errors() {
+			assert_eq!(Error::BalanceOverflow.reason(), "BalanceOverflow");
+			assert_eq!(
+				Error::Extension(ExtensionError::InsufficientBalance).reason(),
+				"InsufficientBalance"
+			);
+
+			// Error(string) selector, then the SCALE encoded string solang expects on substrate
+			let data = (ERROR_SELECTOR, Error::InsufficientAllowance.reason()).encode();
+			assert_eq!(data[..4], [0x08, 0xc3, 0x79, 0xa0]);
+			assert_eq!(<String>::decode(&mut &data[4..]).unwrap(), "InsufficientAllowance");
+		}
+
+		#[ink::test]
+		fn batch_transfer_works() {
+			let (mut token, _) = setup();
+			let (alice, bob, charlie) = (accounts().alice, accounts().bob, accounts().charlie);
+
+			assert_eq!(token.max_batch_size(), DEFAULT_MAX_BATCH_SIZE);
+			assert_eq!(
+				token.batch_transfer_impl(vec![(bob, U256::from(10)), (charlie, U256::from(20))]),
+				Ok(())
+			);
+			assert_eq!(token.balance_of(alice), U256::from(970));
+			assert_eq!(token.balance_of(bob), U256::from(10));
+			assert_eq!(token.balance_of(charlie), U256::from(20));
+
+			let events = events();
+			assert_eq!(events.len(), 2);
+			assert_transfer(&events[0], Some(alice), Some(bob), 10);
+			assert_transfer(&events[1], Some(alice), Some(charlie), 20);
+		}
+
+		#[ink::test]
+		fn batch_transfer_checks_the_whole_batch_first() {
+			let (mut token, _) = setup();
+			let bob = accounts().bob;
+
+			let batch = vec![(bob, U256::one()); DEFAULT_MAX_BATCH_SIZE as usize + 1];
+			assert_eq!(token.batch_transfer_impl(batch), Err(Error::BatchTooLarge));
+
+			let batch = vec![(bob, U256::one()), (bob, U256::MAX)];
+			assert_eq!(token.batch_transfer_impl(batch), Err(Error::BalanceOverflow));
+
+			assert_eq!(token.balance_of(bob), U256::zero());
+			assert!(events().is_empty());
+		}
+
+		#[ink::test]
+		fn batch_transfer_reports_the_failed_transfer() {
+			let (mut token, _) = setup();
+			let bob = accounts().bob;
+
+			// on-chain the `Err` also reverts the first transfer
+			let batch = vec![(bob, U256::from(600)), (bob, U256::from(600))];
+			assert_eq!(
+				token.batch_transfer_impl(batch),
+				Err(Error::Extension(ExtensionError::InsufficientBalance))
+			);
+		}
+
+		#[ink::test]
+		fn batch_size_is_configurable() {
+			let (_, _) = setup();
+			let mut token = NativeToken::create_wrapper_token_with_batch_size(TOKEN_ID, 1);
+			let (bob, charlie) = (accounts().bob, accounts().charlie);
+
+			assert_eq!(token.max_batch_size(), 1);
+			assert_eq!(token.batch_transfer_impl(vec![(bob, U256::one())]), Ok(()));
+			assert_eq!(
+				token.batch_transfer_impl(vec![(bob, U256::one()), (charlie, U256::one())]),
+				Err(Error::BatchTooLarge)
+			);
+		}
+
+		#[ink::test]
+		fn batch_size_defaults_without_its_key() {
+			let (token, _) = setup();
+			// as in a wrapper instantiated before the bound existed, then upgraded in place
+			ink_env::clear_contract_storage(&Key::from(MAX_BATCH_SIZE_KEY));
+			assert_eq!(token.max_batch_size(), DEFAULT_MAX_BATCH_SIZE);
+		}
+
+		#[ink::test]
+		fn approve_overwrites_allowance() {
+			let (mut token, _) = setup();
+			let (alice, bob) = (accounts().alice, accounts().bob);
+
+			assert_eq!(token.allowance(alice, bob), U256::zero());
+			assert!(token.approve(bob, U256::from(20)));
+			assert_eq!(token.allowance(alice, bob), U256::from(20));
+			assert!(token.approve(bob, U256::from(5)));
+			assert_eq!(token.allowance(alice, bob), U256::from(5));
+
+			// allowances are stored as U256, beyond what the runtime can move
+			assert!(token.approve(bob, U256::MAX));
+			assert_eq!(token.allowance(alice, bob), U256::MAX);
+			assert_eq!(token.allowance(bob, alice), U256::zero());
+
+			let events = events();
+			assert_eq!(events.len(), 3);
+			assert_approval(&events[0], alice, bob, U256::from(20));
+			assert_approval(&events[2], alice, bob, U256::MAX);
+		}
+
+		#[ink::test]
+		fn transfer_from_spends_allowance() {
+			let (mut token, ledger) = setup();
+			let (alice, bob, charlie) = (accounts().alice, accounts().bob, accounts().charlie);
+
+			assert!(token.approve(bob, U256::from(20)));
+
+			set_caller(&ledger, bob);
+			assert!(token.transfer_from(alice, charlie, U256::from(15)));
+			assert_eq!(token.allowance(alice, bob), U256::from(5));
+			assert_eq!(token.balance_of(alice), U256::from(985));
+			assert_eq!(token.balance_of(charlie), U256::from(15));
+
+			// beyond the allowance
+			assert!(!token.transfer_from(alice, charlie, U256::from(6)));
+
+			// the allowance isn't spent when the runtime refuses the transfer
+			ledger.borrow_mut().fail_with.insert(207, 2);
+			assert!(!token.transfer_from(alice, charlie, U256::from(5)));
+			assert_eq!(token.allowance(alice, bob), U256::from(5));
+
+			let events = events();
+			assert_eq!(events.len(), 2);
+			assert_transfer(&events[1], Some(alice), Some(charlie), 15);
+		}
+
+		#[ink::test]
+		fn transfer_from_rejects_values_beyond_u128() {
+			let (mut token, ledger) = setup();
+			let (alice, bob) = (accounts().alice, accounts().bob);
+
+			assert!(token.approve(bob, U256::MAX));
+
+			set_caller(&ledger, bob);
+			assert!(!token.transfer_from(alice, bob, U256::from(u128::MAX) + 1));
+			assert_eq!(token.allowance(alice, bob), U256::MAX);
+			assert_eq!(token.balance_of(alice), U256::from(1000));
+		}
+
+		#[ink::test]
+		fn increase_and_decrease_allowance() {
+			let (mut token, _) = setup();
+			let (alice, bob) = (accounts().alice, accounts().bob);
+
+			assert!(token.increase_allowance(bob, U256::from(20)));
+			assert!(token.increase_allowance(bob, U256::from(5)));
+			assert_eq!(token.allowance(alice, bob), U256::from(25));
+			assert!(token.decrease_allowance(bob, U256::from(10)));
+			assert_eq!(token.allowance(alice, bob), U256::from(15));
+
+			// neither below zero nor beyond U256::MAX
+			assert!(!token.decrease_allowance(bob, U256::from(16)));
+			assert!(!token.increase_allowance(bob, U256::MAX));
+			assert_eq!(token.allowance(alice, bob), U256::from(15));
+
+			let events = events();
+			assert_eq!(events.len(), 3);
+			assert_approval(&events[0], alice, bob, U256::from(20));
+			assert_approval(&events[1], alice, bob, U256::from(25));
+			assert_approval(&events[2], alice, bob, U256::from(15));
+		}
+
+		#[ink::test]
+		fn infinite_allowance_never_decreases() {
+			let (mut token, ledger) = setup();
+			let (alice, bob, charlie) = (accounts().alice, accounts().bob, accounts().charlie);
+
+			assert!(token.approve(bob, U256::MAX));
+
+			set_caller(&ledger, bob);
+			assert!(token.transfer_from(alice, charlie, U256::from(15)));
+			assert!(token.transfer_from(alice, charlie, U256::from(15)));
+			assert_eq!(token.allowance(alice, bob), U256::MAX);
+			assert_eq!(token.balance_of(charlie), U256::from(30));
+
+			// it can still be lowered explicitly
+			set_caller(&ledger, alice);
+			assert!(token.decrease_allowance(bob, U256::one()));
+			set_caller(&ledger, bob);
+			assert!(token.transfer_from(alice, charlie, U256::from(15)));
+			assert_eq!(token.allowance(alice, bob), U256::MAX - 16);
+		}
+
+		#[ink::test]
+		#[cfg(feature = "mint-burn")]
+		fn mint_and_burn_change_the_supply() {
+			let (mut token, _) = setup();
+			let bob = accounts().bob;
+
+			assert!(token.mint(bob, U256::from(50)));
+			assert_eq!(token.total_supply(), U256::from(1050));
+			assert!(token.burn(bob, U256::from(20)));
+			assert_eq!(token.total_supply(), U256::from(1030));
+			assert_eq!(token.balance_of(bob), U256::from(30));
+
+			// InsufficientBalance
+			assert!(!token.burn(bob, U256::from(31)));
+
+			let events = events();
+			assert_eq!(events.len(), 2);
+			assert_transfer(&events[0], None, Some(bob), 50);
+			assert_transfer(&events[1], Some(bob), None, 20);
+		}
+
+		#[ink::test]
+		#[cfg(feature = "mint-burn")]
+		fn mint_and_burn_require_a_role() {
+			let (mut token, ledger) = setup();
+			let bob = accounts().bob;
+
+			set_caller(&ledger, bob);
+			assert!(!token.mint(bob, U256::from(50)));
+			assert!(!token.burn(accounts().alice, U256::from(50)));
+			assert_eq!(
+				token.mint_impl(bob, U256::one()),
+				Err(Error::Extension(ExtensionError::AccessDenied))
+			);
+			assert_eq!(
+				token.burn_impl(accounts().alice, U256::one()),
+				Err(Error::Extension(ExtensionError::AccessDenied))
+			);
+
+			// values beyond u128 never reach the runtime
+			set_caller(&ledger, accounts().alice);
+			assert!(!token.mint(bob, U256::MAX));
+
+			assert_eq!(token.total_supply(), U256::from(1000));
+			assert!(events().is_empty());
+		}
+
+		#[ink::test]
+		#[cfg(not(feature = "mint-burn"))]
+		fn mint_and_burn_are_unsupported_without_the_feature() {
+			let (mut token, _) = setup();
+			let bob = accounts().bob;
+
+			// even for ALICE who holds the roles in the mocked runtime
+			assert!(!token.mint(bob, U256::from(50)));
+			assert_eq!(token.mint_impl(bob, U256::one()), Err(Error::Unsupported));
+			assert_eq!(token.burn_impl(accounts().alice, U256::one()), Err(Error::Unsupported));
+
+			assert_eq!(token.total_supply(), U256::from(1000));
+			assert!(events().is_empty());
+		}
 	}
 }
@@ -0,0 +1,374 @@
+//! Command line front-end to `generic_client::Contract`.
+//!
+//! ```bash
+//! contracts-hub deploy --bundle ../contracts/flipper.contract --constructor new --args true
+//! contracts-hub call --bundle ../contracts/flipper.contract --address 5F... --message flip
+//! contracts-hub read --bundle ../contracts/flipper.contract --address 5F... --message get
+//! ```
+
+use std::path::PathBuf;
+
+use clap::{ArgEnum, Parser, Subcommand};
+use contract_transcode::ContractMessageTranscoder;
+use serde_json::json;
+use sp_core::{sr25519, Pair, H256};
+use subxt_tests::{
+    generic_client::{
+        layout::Decoded,
+        manifest::{parse_address, Manifest},
+        Contract,
+    },
+    node, API,
+};
+
+#[derive(Parser)]
+#[clap(
+    name = "contracts-hub",
+    about = "deploy and interact with contracts on laguna-chain"
+)]
+struct Cli {
+    /// websocket endpoint of the node
+    #[clap(long, env = "ENDPOINT", default_value = "ws://127.0.0.1:9944")]
+    url: String,
+
+    /// secret uri of the signer, e.g. `//Alice` or a mnemonic
+    #[clap(long, default_value = "//Alice")]
+    suri: String,
+
+    #[clap(long, arg_enum, default_value = "human")]
+    output: Output,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ArgEnum)]
+enum Output {
+    Human,
+    Json,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// upload the wasm blob of a bundle without instantiating it
+    Upload {
+        #[clap(long)]
+        bundle: PathBuf,
+    },
+    /// upload and instantiate a bundle
+    Deploy {
+        #[clap(long)]
+        bundle: PathBuf,
+        #[clap(long, default_value = "new")]
+        constructor: String,
+        #[clap(long, multiple_values = true)]
+        args: Vec<String>,
+        #[clap(long, default_value_t = 0)]
+        value: u128,
+    },
+    /// send a message as an extrinsic
+    Call {
+        #[clap(long)]
+        bundle: PathBuf,
+        #[clap(long)]
+        address: String,
+        #[clap(long)]
+        message: String,
+        #[clap(long, multiple_values = true)]
+        args: Vec<String>,
+        #[clap(long, default_value_t = 0)]
+        value: u128,
+    },
+    /// dry-run a message and decode its return value
+    Read {
+        #[clap(long)]
+        bundle: PathBuf,
+        #[clap(long)]
+        address: String,
+        #[clap(long)]
+        message: String,
+        #[clap(long, multiple_values = true)]
+        args: Vec<String>,
+        #[clap(long, default_value_t = 0)]
+        value: u128,
+    },
+    /// read storage cells described by the layout, or the raw child trie
+    Storage {
+        #[clap(long)]
+        bundle: PathBuf,
+        #[clap(long)]
+        address: String,
+        /// field path in the storage layout, e.g. `balances.total`; all cells if omitted
+        #[clap(long)]
+        path: Option<String>,
+        /// dump every raw key/value pair instead of decoding the layout
+        #[clap(long)]
+        raw: bool,
+    },
+    /// deploy every contract of a manifest in dependency order
+    World {
+        #[clap(long)]
+        manifest: PathBuf,
+        /// where to write the resulting address book as JSON
+        #[clap(long)]
+        address_book: Option<PathBuf>,
+    },
+    /// decode the events emitted by a contract in a block
+    Events {
+        #[clap(long)]
+        bundle: PathBuf,
+        #[clap(long)]
+        address: String,
+        /// block hash, the best block if omitted
+        #[clap(long)]
+        block: Option<String>,
+    },
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+
+    let api = API::from_url(&cli.url).await?;
+    let signer = sr25519::Pair::from_string(&cli.suri, None)
+        .map_err(|e| anyhow::anyhow!("invalid suri: {e:?}"))?;
+
+    match cli.command {
+        Command::Upload { bundle } => {
+            let contract = Contract::new(&bundle)?;
+            contract.upload_code(&api, signer).await?;
+
+            print(cli.output, "uploaded", json!({ "bundle": bundle }));
+        }
+        Command::Deploy {
+            bundle,
+            constructor,
+            args,
+            value,
+        } => {
+            let mut contract = Contract::new(&bundle)?;
+            let data =
+                ContractMessageTranscoder::new(contract.project()).encode(&constructor, &args)?;
+
+            let events = contract
+                .deploy(&api, signer, value, |_| data.clone())
+                .await?;
+            let address = contract.address().cloned().unwrap();
+
+            print(
+                cli.output,
+                &format!("deployed at {address}"),
+                json!({
+                    "address": address.to_string(),
+                    "events": decode_events(&contract, &events),
+                }),
+            );
+        }
+        Command::Call {
+            bundle,
+            address,
+            message,
+            args,
+            value,
+        } => {
+            let contract = Contract::new(&bundle)?.from_addr(parse_address(&address)?)?;
+            let data =
+                ContractMessageTranscoder::new(contract.project()).encode(&message, &args)?;
+
+            let out = contract
+                .call_with_fee(&api, signer, value, |_| data.clone())
+                .await?;
+            let events = decode_events(&contract, &out.events);
+
+            let mut human = format!(
+                "fee: {} (tip {}), weight: {}",
+                out.fee.actual_fee, out.fee.tip, out.fee.weight
+            );
+            for e in &events {
+                human.push_str(&format!("\n{}", e.as_str().unwrap_or_default()));
+            }
+
+            print(
+                cli.output,
+                &human,
+                json!({
+                    "fee": out.fee.actual_fee.to_string(),
+                    "tip": out.fee.tip.to_string(),
+                    "weight": out.fee.weight,
+                    "events": events,
+                }),
+            );
+        }
+        Command::Read {
+            bundle,
+            address,
+            message,
+            args,
+            value,
+        } => {
+            let contract = Contract::new(&bundle)?.from_addr(parse_address(&address)?)?;
+            let transcoder = ContractMessageTranscoder::new(contract.project());
+            let data = transcoder.encode(&message, &args)?;
+
+            let rv = contract
+                .try_call(&api, signer, value, |_| data.clone())
+                .await?;
+            let decoded = transcoder.decode_return(&message, &mut rv.as_slice())?;
+
+            print(
+                cli.output,
+                &decoded.to_string(),
+                json!({ "return_value": decoded.to_string() }),
+            );
+        }
+        Command::Storage {
+            bundle,
+            address,
+            path,
+            raw,
+        } => {
+            let contract = Contract::new(&bundle)?.from_addr(parse_address(&address)?)?;
+
+            if raw {
+                let snapshot = contract.snapshot(&api).await?;
+                let entries = snapshot
+                    .entries
+                    .iter()
+                    .map(|(k, v)| {
+                        (
+                            format!("0x{}", hex::encode(k)),
+                            format!("0x{}", hex::encode(v)),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+
+                let human = entries
+                    .iter()
+                    .map(|(k, v)| format!("{k}: {v}"))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+
+                print(
+                    cli.output,
+                    &human,
+                    json!({
+                        "at": format!("{:?}", snapshot.at),
+                        "entries": entries
+                            .into_iter()
+                            .map(|(k, v)| (k, json!(v)))
+                            .collect::<serde_json::Map<_, _>>(),
+                    }),
+                );
+            } else {
+                let layout = contract.storage_layout();
+                let cells = match path {
+                    Some(p) => vec![(p.clone(), layout.field(&p)?)],
+                    None => layout.cells(),
+                };
+
+                let mut values = Vec::new();
+                for (name, cell) in cells {
+                    let v = contract.read_cell_decoded(&api, &cell).await?;
+                    values.push((name, v.as_ref().map(Decoded::to_string)));
+                }
+
+                let human = values
+                    .iter()
+                    .map(|(k, v)| format!("{k}: {}", v.as_deref().unwrap_or("<empty>")))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+
+                print(
+                    cli.output,
+                    &human,
+                    serde_json::Value::Object(
+                        values.into_iter().map(|(k, v)| (k, json!(v))).collect(),
+                    ),
+                );
+            }
+        }
+        Command::World {
+            manifest,
+            address_book,
+        } => {
+            let world = Manifest::load(&manifest)?.deploy(&api, &signer).await?;
+
+            if let Some(path) = address_book {
+                world.addresses().write(path)?;
+            }
+
+            let human = world
+                .addresses()
+                .iter()
+                .map(|(k, v)| format!("{k}: {v}"))
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            print(cli.output, &human, world.addresses().to_json());
+        }
+        Command::Events {
+            bundle,
+            address,
+            block,
+        } => {
+            let contract = Contract::new(&bundle)?.from_addr(parse_address(&address)?)?;
+            let at = block.as_deref().map(parse_hash).transpose()?;
+
+            let events = api
+                .events()
+                .at(at)
+                .await?
+                .find::<node::contracts::events::ContractEmitted>()
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter()
+                .filter(|e| Some(&e.contract) == contract.address())
+                .collect::<Vec<_>>();
+            let events = decode_events(&contract, &events);
+
+            let human = events
+                .iter()
+                .map(|e| e.as_str().unwrap_or_default().to_string())
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            print(cli.output, &human, json!({ "events": events }));
+        }
+    }
+
+    Ok(())
+}
+
+fn print(output: Output, human: &str, json: serde_json::Value) {
+    match output {
+        Output::Human => println!("{human}"),
+        Output::Json => println!("{json}"),
+    }
+}
+
+fn parse_hash(s: &str) -> anyhow::Result<H256> {
+    let raw = hex::decode(s.trim_start_matches("0x"))?;
+
+    if raw.len() != 32 {
+        return Err(anyhow::anyhow!("invalid block hash {s}"));
+    }
+
+    Ok(H256::from_slice(&raw))
+}
+
+/// decode event payloads with the bundle's metadata, falling back to hex for unknown events
+fn decode_events(
+    contract: &Contract,
+    events: &[node::contracts::events::ContractEmitted],
+) -> Vec<serde_json::Value> {
+    let transcoder = ContractMessageTranscoder::new(contract.project());
+
+    events
+        .iter()
+        .map(
+            |e| match transcoder.decode_contract_event(&mut e.data.as_slice()) {
+                Ok(v) => json!(v.to_string()),
+                Err(_) => json!(format!("0x{}", hex::encode(&e.data))),
+            },
+        )
+        .collect()
+}
@@ -0,0 +1,77 @@
+use contract_transcode::ContractMessageTranscoder;
+use parity_scale_codec::{Decode, Encode};
+use sp_core::{crypto::AccountId32, ecdsa, hexdisplay::AsBytesRef, Pair, U256};
+use sp_keyring::AccountKeyring;
+use subxt::tx::PairSigner;
+
+use crate::eth_client::rpc::EthErpcWrapper;
+use crate::generic_client::{system::ENV_UTILS, Contract};
+use crate::utils::{account_id_to_eth, eth_to_account_id, free_balance_of, to_eth_address};
+use crate::{node, API};
+
+#[tokio::test]
+async fn eth_address_maps_to_its_balance_account() -> anyhow::Result<()> {
+    let alice = PairSigner::new(AccountKeyring::Alice.pair());
+
+    let api = API::from_url(
+        std::env::var("ENDPOINT").unwrap_or_else(|_| "ws://127.0.0.1:9944".to_string()),
+    )
+    .await?;
+
+    let eth_client_wrapper = EthErpcWrapper(api.clone());
+
+    let pair = ecdsa::Pair::from_string("//AccountMapping", None).unwrap();
+    let eth_addr = to_eth_address(pair.public())?;
+
+    // fund the ethereum address through the runtime so it shows up on both sides
+    let prefund = node::tx().evm_compat().transfer(
+        Decode::decode(&mut &eth_addr.encode()[..])?,
+        10_u128.pow(18),
+    );
+
+    api.tx()
+        .sign_and_submit_then_watch_default(&prefund, &alice)
+        .await?
+        .wait_for_in_block()
+        .await?;
+
+    // the mapped account holds exactly the balance seen through the eth rpc
+    let account = eth_to_account_id(&api, eth_addr).await?;
+
+    let eth_balance = eth_client_wrapper.get_balance(eth_addr, None).await?;
+    let free = free_balance_of(&api, account.clone()).await?;
+
+    assert_eq!(eth_balance, U256::from(free));
+    assert!(free >= 10_u128.pow(18));
+
+    // nobody registered the default account as a proxy
+    assert_eq!(account_id_to_eth(&api, &account).await?, None);
+
+    // env_utils derives the same default account on-chain
+    let mut env_utils = Contract::new("../contracts/env_utils.contract")?;
+    env_utils
+        .ensure_system_contract(&api, ENV_UTILS, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>("new", []).unwrap()
+        })
+        .await?;
+
+    let rv = env_utils
+        .try_call(
+            &api,
+            AccountKeyring::Alice,
+            0,
+            &|_: ContractMessageTranscoder<'_>| {
+                // default_account_id(bytes20)
+                [0xab, 0x7b, 0x23, 0x3e]
+                    .into_iter()
+                    .chain(eth_addr.0)
+                    .collect()
+            },
+        )
+        .await?;
+
+    let onchain = <[u8; 32]>::decode(&mut rv.as_bytes_ref())?;
+    assert_eq!(AccountId32::new(onchain), account);
+
+    Ok(())
+}
@@ -1 +1,2 @@
+pub mod account_mapping;
 pub mod issue666;
@@ -1,6 +1,18 @@
+pub mod batch;
 pub mod cases;
-
-use std::{borrow::Borrow, path::Path};
+pub mod fixture;
+pub mod fuzz;
+pub mod gas;
+pub mod layout;
+pub mod manifest;
+pub mod snapshot;
+pub mod solang_storage;
+pub mod system;
+
+use std::{
+    borrow::Borrow,
+    path::{Path, PathBuf},
+};
 
 use contract_transcode::ContractMessageTranscoder;
 use ink_metadata::{InkProject, MetadataVersioned};
@@ -10,11 +22,18 @@ use crate::{
     API,
 };
 use jsonschema::JSONSchema;
+use layout::{Decoded, InkLayout, StorageCell};
 use once_cell::sync::Lazy;
-use pallet_contracts_primitives::{ContractResult, ExecReturnValue, GetStorageResult};
+use pallet_contracts_primitives::{
+    Code, ContractResult, ExecReturnValue, GetStorageResult, InstantiateReturnValue,
+};
 use parity_scale_codec::{Decode, Encode};
+use snapshot::{Labels, Snapshot};
+use solang_storage::{SolangSlot, SolangStorage};
 
-use sp_core::{crypto::AccountId32, hexdisplay::AsBytesRef, Bytes};
+use sp_core::{
+    blake2_256, crypto::AccountId32, hexdisplay::AsBytesRef, sr25519, Bytes, Pair as _, H256,
+};
 use subxt::{
     ext::sp_runtime::DispatchError,
     rpc::{rpc_params, ClientT},
@@ -26,20 +45,47 @@ use contract_metadata::ContractMetadata;
 use sp_keyring::AccountKeyring;
 use tokio::time::timeout;
 
-pub struct DeployContract {
-    pub caller: AccountKeyring,
+/// Anything that can sign extrinsics and act as the origin of a dry-run.
+pub trait Caller: Send + Sync {
+    fn pair(&self) -> sr25519::Pair;
+
+    fn account_id(&self) -> AccountId32 {
+        self.pair().public().into()
+    }
+}
+
+impl Caller for AccountKeyring {
+    fn pair(&self) -> sr25519::Pair {
+        AccountKeyring::pair(*self)
+    }
+}
+
+impl Caller for sr25519::Pair {
+    fn pair(&self) -> sr25519::Pair {
+        self.clone()
+    }
+}
+
+impl<C: Caller> Caller for &C {
+    fn pair(&self) -> sr25519::Pair {
+        (*self).pair()
+    }
+}
+
+pub struct DeployContract<C = AccountKeyring> {
+    pub caller: C,
     pub selector: Vec<u8>,
     pub value: u128,
     pub code: Vec<u8>,
 }
-pub struct WriteContract {
-    pub caller: AccountKeyring,
+pub struct WriteContract<C = AccountKeyring> {
+    pub caller: C,
     pub contract_address: AccountId32,
     pub selector: Vec<u8>,
     pub value: u128,
 }
-pub struct ReadContract {
-    pub caller: AccountKeyring,
+pub struct ReadContract<C = AccountKeyring> {
+    pub caller: C,
     pub contract_address: AccountId32,
     pub value: u128,
     pub selector: Vec<u8>,
@@ -62,9 +108,21 @@ pub mod output {
     pub struct Deployed {
         pub contract_address: AccountId32,
         pub events: Vec<node::contracts::events::ContractEmitted>,
+        pub fee: FeeInfo,
     }
     pub struct WriteSuccess {
         pub events: Vec<node::contracts::events::ContractEmitted>,
+        pub fee: FeeInfo,
+    }
+    /// what an extrinsic actually cost, as reported by its own events
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct FeeInfo {
+        pub payer: AccountId32,
+        /// the fee charged after refunds, tip included
+        pub actual_fee: u128,
+        pub tip: u128,
+        /// the weight actually consumed
+        pub weight: u64,
     }
     pub struct ReadSuccess {
         pub return_value: Vec<u8>,
@@ -79,7 +137,7 @@ fn random_salt() -> Vec<u8> {
 }
 
 #[async_trait::async_trait]
-impl Execution for DeployContract {
+impl<C: Caller> Execution for DeployContract<C> {
     type Output = output::Deployed;
 
     async fn execute(self, api: &API) -> Result<Self::Output, anyhow::Error> {
@@ -90,9 +148,21 @@ impl Execution for DeployContract {
             value,
         } = self;
 
-        let evts = raw_instantiate_and_upload(
+        if gas::enabled() {
+            let rv =
+                dry_run_instantiate(api, &caller, value, code.clone(), selector.clone()).await?;
+            gas::record(
+                blake2_256(&code),
+                &selector,
+                rv.gas_consumed,
+                rv.gas_required,
+                &rv.storage_deposit,
+            )?;
+        }
+
+        let (evts, fee) = raw_instantiate_and_upload(
             api,
-            caller,
+            &caller,
             value,
             GAS_LIMIT,
             None,
@@ -127,12 +197,13 @@ impl Execution for DeployContract {
         Ok(output::Deployed {
             contract_address,
             events,
+            fee,
         })
     }
 }
 
 #[async_trait::async_trait]
-impl Execution for WriteContract {
+impl<C: Caller> Execution for WriteContract<C> {
     type Output = output::WriteSuccess;
 
     async fn execute(self, api: &API) -> Result<Self::Output, anyhow::Error> {
@@ -143,10 +214,28 @@ impl Execution for WriteContract {
             value,
         } = self;
 
-        let evts = raw_call(
+        if gas::enabled() {
+            let rv = dry_run_call(
+                api,
+                &caller,
+                contract_address.clone(),
+                value,
+                selector.clone(),
+            )
+            .await?;
+            gas::record(
+                code_hash_of(api, &contract_address).await?,
+                &selector,
+                rv.gas_consumed,
+                rv.gas_required,
+                &rv.storage_deposit,
+            )?;
+        }
+
+        let (evts, fee) = raw_call(
             api,
             contract_address,
-            caller,
+            &caller,
             value,
             GAS_LIMIT,
             None,
@@ -180,12 +269,12 @@ impl Execution for WriteContract {
             })
             .collect::<Vec<_>>();
 
-        Ok(output::WriteSuccess { events })
+        Ok(output::WriteSuccess { events, fee })
     }
 }
 
 #[async_trait::async_trait]
-impl Execution for ReadContract {
+impl<C: Caller> Execution for ReadContract<C> {
     type Output = output::ReadSuccess;
 
     async fn execute(self, api: &API) -> Result<Self::Output, anyhow::Error> {
@@ -196,7 +285,26 @@ impl Execution for ReadContract {
             value,
         } = self;
 
-        let rv = read_call(api, caller, contract_address, value, selector).await?;
+        let code_hash = if gas::enabled() {
+            Some(code_hash_of(api, &contract_address).await?)
+        } else {
+            None
+        };
+
+        // a read is a dry-run already, profile it from the same result
+        let rv = dry_run_call(api, &caller, contract_address, value, selector.clone()).await?;
+
+        if let Some(code_hash) = code_hash {
+            gas::record(
+                code_hash,
+                &selector,
+                rv.gas_consumed,
+                rv.gas_required,
+                &rv.storage_deposit,
+            )?;
+        }
+
+        let rv = exec_result(api, rv.result)?;
 
         if rv.did_revert() {
             Err(anyhow::anyhow!("reverted"))
@@ -234,15 +342,15 @@ pub struct CallRequest {
 
 async fn raw_instantiate_and_upload(
     api: &API,
-    builtin_keyring: sp_keyring::AccountKeyring,
+    caller: &impl Caller,
     value: u128,
     gas_limit: u64,
     storage_deposit_limit: Option<u128>,
     code: Vec<u8>,
     data: Vec<u8>,
     salt: Vec<u8>,
-) -> anyhow::Result<TxEvents<PolkadotConfig>> {
-    let signer = PairSigner::new(builtin_keyring.pair());
+) -> anyhow::Result<(TxEvents<PolkadotConfig>, output::FeeInfo)> {
+    let signer = PairSigner::new(caller.pair());
 
     let payload = node::tx().contracts().instantiate_with_code(
         value,
@@ -261,17 +369,18 @@ async fn raw_instantiate_and_upload(
         .await?
         .fetch_events()
         .await?;
+    let fee = fee_info(&evt)?;
 
-    Ok(evt)
+    Ok((evt, fee))
 }
 
 async fn raw_upload(
     api: &API,
-    builtin_keyring: sp_keyring::AccountKeyring,
+    caller: &impl Caller,
     storage_deposit_limit: Option<u128>,
     code: Vec<u8>,
-) -> anyhow::Result<TxEvents<PolkadotConfig>> {
-    let signer = PairSigner::new(builtin_keyring.pair());
+) -> anyhow::Result<(TxEvents<PolkadotConfig>, output::FeeInfo)> {
+    let signer = PairSigner::new(caller.pair());
 
     let payload = node::tx().contracts().upload_code(code, None);
 
@@ -283,8 +392,32 @@ async fn raw_upload(
         .await?
         .fetch_events()
         .await?;
+    let fee = fee_info(&evt)?;
+
+    Ok((evt, fee))
+}
 
-    Ok(evt)
+/// Pick the fee payment and the consumed weight out of the events of one extrinsic.
+fn fee_info(evts: &TxEvents<PolkadotConfig>) -> anyhow::Result<output::FeeInfo> {
+    let paid = evts
+        .find_first::<node::transaction_payment::events::TransactionFeePaid>()?
+        .ok_or_else(|| anyhow::anyhow!("unable to find fee payment"))?;
+
+    // a failed extrinsic still pays for the weight it used
+    let weight = match evts.find_first::<node::system::events::ExtrinsicSuccess>()? {
+        Some(e) => e.dispatch_info.weight,
+        None => evts
+            .find_first::<node::system::events::ExtrinsicFailed>()?
+            .map(|e| e.dispatch_info.weight)
+            .ok_or_else(|| anyhow::anyhow!("unable to find extrinsic result"))?,
+    };
+
+    Ok(output::FeeInfo {
+        payer: paid.who,
+        actual_fee: paid.actual_fee,
+        tip: paid.tip,
+        weight,
+    })
 }
 
 const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
@@ -292,13 +425,13 @@ const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
 async fn raw_call(
     api: &API,
     dest: AccountId32,
-    builtin_keyring: sp_keyring::AccountKeyring,
+    caller: &impl Caller,
     value: u128,
     gas_limit: u64,
     storage_deposit_limit: Option<u128>,
     data: Vec<u8>,
-) -> anyhow::Result<TxEvents<PolkadotConfig>> {
-    let signer = PairSigner::new(builtin_keyring.pair());
+) -> anyhow::Result<(TxEvents<PolkadotConfig>, output::FeeInfo)> {
+    let signer = PairSigner::new(caller.pair());
 
     let payload = node::tx().contracts().call(
         subxt::ext::sp_runtime::MultiAddress::Id(dest),
@@ -318,8 +451,9 @@ async fn raw_call(
             .fetch_events(),
     )
     .await??;
+    let fee = fee_info(&evt)?;
 
-    Ok(evt)
+    Ok((evt, fee))
 }
 
 async fn query_call(
@@ -336,15 +470,26 @@ async fn query_call(
     <GetStorageResult>::decode(&mut rv.as_bytes_ref()).map_err(|e| anyhow::anyhow!("{e:?}"))
 }
 
-async fn read_call(
+#[derive(Encode)]
+pub struct InstantiateRequest {
+    origin: <PolkadotConfig as Config>::AccountId,
+    value: u128,
+    gas_limit: u64,
+    storage_deposit_limit: Option<u128>,
+    code: Code<<PolkadotConfig as Config>::Hash>,
+    data: Vec<u8>,
+    salt: Vec<u8>,
+}
+
+async fn dry_run_call(
     api: &API,
-    caller: AccountKeyring,
+    caller: &impl Caller,
     contract_address: AccountId32,
     value: u128,
     selector: Vec<u8>,
-) -> anyhow::Result<ExecReturnValue> {
+) -> anyhow::Result<ContractResult<Result<ExecReturnValue, DispatchError>, u128>> {
     let req = CallRequest {
-        origin: caller.into(),
+        origin: caller.account_id(),
         dest: contract_address,
         value,
         gas_limit: GAS_LIMIT,
@@ -355,11 +500,51 @@ async fn read_call(
     let params = rpc_params!["ContractsApi_call", Bytes(req.encode())];
     let rv: Bytes = api.rpc().client.request("state_call", params).await?;
 
-    let rv = ContractResult::<Result<ExecReturnValue, DispatchError>, u128>::decode(
-        &mut rv.as_bytes_ref(),
-    )?;
+    ContractResult::decode(&mut rv.as_bytes_ref()).map_err(Into::into)
+}
+
+async fn dry_run_instantiate(
+    api: &API,
+    caller: &impl Caller,
+    value: u128,
+    code: Vec<u8>,
+    data: Vec<u8>,
+) -> anyhow::Result<ContractResult<Result<InstantiateReturnValue<AccountId32>, DispatchError>, u128>>
+{
+    let req = InstantiateRequest {
+        origin: caller.account_id(),
+        value,
+        gas_limit: GAS_LIMIT,
+        storage_deposit_limit: None,
+        code: Code::Upload(code.into()),
+        data,
+        salt: random_salt(),
+    };
+
+    let params = rpc_params!["ContractsApi_instantiate", Bytes(req.encode())];
+    let rv: Bytes = api.rpc().client.request("state_call", params).await?;
+
+    ContractResult::decode(&mut rv.as_bytes_ref()).map_err(Into::into)
+}
 
-    rv.result.map_err(|e| {
+async fn read_call(
+    api: &API,
+    caller: &impl Caller,
+    contract_address: AccountId32,
+    value: u128,
+    selector: Vec<u8>,
+) -> anyhow::Result<ExecReturnValue> {
+    let rv = dry_run_call(api, caller, contract_address, value, selector).await?;
+
+    exec_result(api, rv.result)
+}
+
+/// turn the dispatch error of a dry-run into a readable one
+fn exec_result(
+    api: &API,
+    result: Result<ExecReturnValue, DispatchError>,
+) -> anyhow::Result<ExecReturnValue> {
+    result.map_err(|e| {
         if let DispatchError::Module(m) = e {
             if let Ok(d) = api.metadata().error(m.index, m.error) {
                 return anyhow::anyhow!("{d:?}");
@@ -370,6 +555,53 @@ async fn read_call(
     })
 }
 
+/// code hash the contract at `address` currently runs
+async fn code_hash_of(api: &API, address: &AccountId32) -> anyhow::Result<[u8; 32]> {
+    let key = node::storage().contracts().contract_info_of(address);
+
+    api.storage()
+        .fetch(&key, None)
+        .await?
+        .map(|info| info.code_hash.0)
+        .ok_or_else(|| anyhow::anyhow!("{address} is not a contract"))
+}
+
+/// upload `code` and point `address` to it through `sudo(Contracts::set_code)`
+async fn sudo_set_code(api: &API, address: &AccountId32, code: Vec<u8>) -> anyhow::Result<()> {
+    use node::runtime_types::{
+        laguna_runtime::Call as RuntimeCall, pallet_contracts::pallet::Call as ContractsCall,
+    };
+
+    let code_hash = H256(blake2_256(&code));
+    raw_upload(api, &AccountKeyring::Alice, None, code).await?;
+
+    let payload = node::tx()
+        .sudo()
+        .sudo(RuntimeCall::Contracts(ContractsCall::set_code {
+            dest: subxt::ext::sp_runtime::MultiAddress::Id(address.clone()),
+            code_hash,
+        }));
+
+    let events = api
+        .tx()
+        .sign_and_submit_then_watch_default(
+            &payload,
+            &PairSigner::new(AccountKeyring::Alice.pair()),
+        )
+        .await?
+        .wait_for_in_block()
+        .await?
+        .fetch_events()
+        .await?;
+    system::ensure_sudid(&events)?;
+
+    events
+        .find_first::<node::contracts::events::ContractCodeUpdated>()?
+        .ok_or_else(|| anyhow::anyhow!("code of {address} was not updated"))?;
+
+    Ok(())
+}
+
 static SCHEMA: Lazy<JSONSchema> = Lazy::new(|| {
     let raw = include_bytes!("../ink-v3-schema.json");
     let val: serde_json::Value = serde_json::from_slice(raw).unwrap();
@@ -387,6 +619,8 @@ fn load_versioned_metadata(contract: &ContractMetadata) -> anyhow::Result<InkPro
     let ink_metadata = serde_json::from_value::<MetadataVersioned>(abi_json)?;
 
     if let MetadataVersioned::V3(project) = ink_metadata {
+        gas::register(contract, &project);
+
         Ok(project)
     } else {
         Err(anyhow::anyhow!("expecting MetadataVersionedV3"))
@@ -401,8 +635,8 @@ pub fn load_project(path: impl AsRef<Path>) -> anyhow::Result<InkProject> {
     load_versioned_metadata(&contract)
 }
 
-struct Contract {
-    path: &'static str,
+pub struct Contract {
+    path: PathBuf,
     project: InkProject,
     blob: Vec<u8>,
     address: Option<AccountId32>,
@@ -410,8 +644,9 @@ struct Contract {
 }
 
 impl Contract {
-    pub fn new(path: &'static str) -> anyhow::Result<Self> {
-        let r = std::fs::File::open(path)?;
+    pub fn new(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let r = std::fs::File::open(&path)?;
 
         let contract: ContractMetadata = serde_json::from_reader(r)?;
         let project = load_versioned_metadata(&contract)?;
@@ -433,19 +668,23 @@ impl Contract {
     }
 
     pub fn from_addr(&self, address: AccountId32) -> anyhow::Result<Self> {
-        let mut out = Contract::new(self.path)?;
+        let mut out = Contract::new(&self.path)?;
 
         out.address.replace(address);
 
         Ok(out)
     }
 
-    pub async fn upload_code(
-        &self,
-        api: &API,
-        caller: sp_keyring::AccountKeyring,
-    ) -> anyhow::Result<()> {
-        raw_upload(api, caller, None, self.blob.clone()).await?;
+    pub fn address(&self) -> Option<&AccountId32> {
+        self.address.as_ref()
+    }
+
+    pub fn project(&self) -> &InkProject {
+        &self.project
+    }
+
+    pub async fn upload_code(&self, api: &API, caller: impl Caller) -> anyhow::Result<()> {
+        raw_upload(api, &caller, None, self.blob.clone()).await?;
 
         Ok(())
     }
@@ -453,7 +692,7 @@ impl Contract {
     pub async fn deploy(
         &mut self,
         api: &API,
-        caller: sp_keyring::AccountKeyring,
+        caller: impl Caller,
         value: u128,
         build_selector: impl Fn(ContractMessageTranscoder<'_>) -> Vec<u8>,
     ) -> anyhow::Result<Vec<node::contracts::events::ContractEmitted>> {
@@ -511,6 +750,7 @@ impl Contract {
             .await?
             .fetch_events()
             .await?;
+        system::ensure_sudid(&runtime_events)?;
 
         let contract_address = runtime_events
             .iter()
@@ -541,33 +781,87 @@ impl Contract {
         Ok(contract_events)
     }
 
+    /// Deploy as a system contract at `destined_address`, or reuse the one already living there,
+    /// replacing its code if it differs from this bundle.
+    pub async fn ensure_system_contract(
+        &mut self,
+        api: &API,
+        destined_address: [u8; 32],
+        value: u128,
+        build_selector: impl Fn(ContractMessageTranscoder<'_>) -> Vec<u8>,
+    ) -> anyhow::Result<()> {
+        let address = AccountId32::from(destined_address);
+
+        if !system::is_system_contract(api, &address).await? {
+            self.deploy_as_system_contract(api, Some(destined_address), value, build_selector)
+                .await?;
+
+            return Ok(());
+        }
+
+        if code_hash_of(api, &address).await? != blake2_256(&self.blob) {
+            sudo_set_code(api, &address, self.blob.clone()).await?;
+        }
+        self.address.replace(address);
+
+        Ok(())
+    }
+
+    /// Replace the code of this contract with the one of `bundle`, keeping its address and
+    /// storage. The returned handle talks to the contract through the new metadata.
+    pub async fn set_code(&self, api: &API, bundle: impl AsRef<Path>) -> anyhow::Result<Contract> {
+        let address = self
+            .address
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("contract is not deployed"))?;
+
+        let mut upgraded = Contract::new(bundle)?;
+        sudo_set_code(api, &address, upgraded.blob.clone()).await?;
+        upgraded.address.replace(address);
+
+        Ok(upgraded)
+    }
+
     pub async fn call(
         &self,
         api: &API,
-        caller: sp_keyring::AccountKeyring,
+        caller: impl Caller,
         value: u128,
         build_selector: impl Fn(ContractMessageTranscoder<'_>) -> Vec<u8>,
     ) -> anyhow::Result<Vec<node::contracts::events::ContractEmitted>> {
+        let out = self
+            .call_with_fee(api, caller, value, build_selector)
+            .await?;
+
+        Ok(out.events)
+    }
+
+    /// like `call`, but also returns the fee charged for the extrinsic
+    pub async fn call_with_fee(
+        &self,
+        api: &API,
+        caller: impl Caller,
+        value: u128,
+        build_selector: impl Fn(ContractMessageTranscoder<'_>) -> Vec<u8>,
+    ) -> anyhow::Result<output::WriteSuccess> {
         let transcoder = ContractMessageTranscoder::new(&self.project);
 
         let selector = build_selector(transcoder);
 
-        let out = WriteContract {
+        WriteContract {
             caller,
             selector,
             value,
             contract_address: self.address.clone().unwrap(),
         }
         .execute(api)
-        .await?;
-
-        Ok(out.events)
+        .await
     }
 
     pub async fn try_call(
         &self,
         api: &API,
-        caller: sp_keyring::AccountKeyring,
+        caller: impl Caller,
         value: u128,
         build_selector: impl Fn(ContractMessageTranscoder<'_>) -> Vec<u8>,
     ) -> anyhow::Result<Vec<u8>> {
@@ -587,6 +881,28 @@ impl Contract {
         Ok(out.return_value)
     }
 
+    /// like `try_call`, but hands back the data of a reverted call as `Err` instead of failing,
+    /// e.g. the encoded error of an ink! message returning `Result`
+    pub async fn try_call_or_revert(
+        &self,
+        api: &API,
+        caller: impl Caller,
+        value: u128,
+        build_selector: impl Fn(ContractMessageTranscoder<'_>) -> Vec<u8>,
+    ) -> anyhow::Result<Result<Vec<u8>, Vec<u8>>> {
+        let transcoder = ContractMessageTranscoder::new(&self.project);
+
+        let selector = build_selector(transcoder);
+
+        let rv = read_call(api, &caller, self.address.clone().unwrap(), value, selector).await?;
+
+        if rv.did_revert() {
+            Ok(Err(rv.data.to_vec()))
+        } else {
+            Ok(Ok(rv.data.to_vec()))
+        }
+    }
+
     pub async fn read_storage(&self, api: &API, key: Vec<u8>) -> anyhow::Result<Option<Vec<u8>>> {
         let out = ReadLayout {
             contract_address: self.address.clone().unwrap(),
@@ -598,4 +914,70 @@ impl Contract {
 
         Ok(out)
     }
+
+    pub fn storage_layout(&self) -> InkLayout<'_> {
+        InkLayout::new(&self.project)
+    }
+
+    pub async fn read_cell<T: Decode>(
+        &self,
+        api: &API,
+        cell: &StorageCell,
+    ) -> anyhow::Result<Option<T>> {
+        self.read_storage(api, cell.key.to_vec())
+            .await?
+            .map(|v| T::decode(&mut v.as_bytes_ref()).map_err(Into::into))
+            .transpose()
+    }
+
+    /// read a cell and decode it with the type recorded in the storage layout
+    pub async fn read_cell_decoded(
+        &self,
+        api: &API,
+        cell: &StorageCell,
+    ) -> anyhow::Result<Option<Decoded>> {
+        self.read_storage(api, cell.key.to_vec())
+            .await?
+            .map(|v| layout::decode_all(self.project.registry(), cell.ty, &v))
+            .transpose()
+    }
+
+    pub async fn read_field<T: Decode>(&self, api: &API, path: &str) -> anyhow::Result<Option<T>> {
+        let cell = self.storage_layout().field(path)?;
+
+        self.read_cell(api, &cell).await
+    }
+
+    pub async fn read_mapping<K: Encode, T: Decode>(
+        &self,
+        api: &API,
+        path: &str,
+        key: &K,
+    ) -> anyhow::Result<Option<T>> {
+        let cell = self.storage_layout().mapping_entry(path, key)?;
+
+        self.read_cell(api, &cell).await
+    }
+
+    pub fn solang_storage(&self) -> anyhow::Result<SolangStorage> {
+        SolangStorage::new(&self.project)
+    }
+
+    /// read a solang storage slot and decode it according to its solidity type
+    pub async fn read_slot(&self, api: &API, slot: &SolangSlot) -> anyhow::Result<Option<Decoded>> {
+        self.read_storage(api, slot.key.to_vec())
+            .await?
+            .map(|v| slot.ty.decode(&v))
+            .transpose()
+    }
+
+    /// dump the whole child trie of the contract at the current best block
+    pub async fn snapshot(&self, api: &API) -> anyhow::Result<Snapshot> {
+        Snapshot::take(api, self.address.as_ref().unwrap()).await
+    }
+
+    /// labels for every storage cell in the metadata layout, to render snapshot diffs with
+    pub fn storage_labels(&self) -> Labels<'_> {
+        Labels::from_layout(&self.storage_layout())
+    }
 }
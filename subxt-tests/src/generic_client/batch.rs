@@ -0,0 +1,75 @@
+//! Payouts to more recipients than a single `native_token_wrapper::batch_transfer` accepts.
+//!
+//! Recipients are split into chunks of the wrapper's `max_batch_size`. Every chunk is atomic on
+//! its own, the payout as a whole is not: when a chunk fails, the chunks before it stay paid.
+
+use contract_transcode::ContractMessageTranscoder;
+use parity_scale_codec::{Decode, Encode};
+use sp_core::{crypto::AccountId32, U256};
+
+use crate::{
+    generic_client::{Caller, Contract},
+    node, API,
+};
+
+pub async fn max_batch_size(api: &API, token: &Contract) -> anyhow::Result<u32> {
+    let rv = token
+        .try_call(
+            api,
+            sp_keyring::AccountKeyring::Alice,
+            0,
+            &|t: ContractMessageTranscoder<'_>| {
+                t.encode::<_, String>("max_batch_size", []).unwrap()
+            },
+        )
+        .await?;
+
+    u32::decode(&mut &rv[..]).map_err(Into::into)
+}
+
+/// Pay every `(to, value)` of `recipients` from `caller` in as few `batch_transfer`s as the
+/// wrapper allows, returning the events of all of them.
+///
+/// Each chunk is dry-run first, so a failing one is reported with the raw revert data of the
+/// wrapper instead of being submitted.
+pub async fn batch_transfer<C: Caller + Clone>(
+    api: &API,
+    token: &Contract,
+    caller: C,
+    recipients: &[(AccountId32, U256)],
+) -> anyhow::Result<Vec<node::contracts::events::ContractEmitted>> {
+    let size = max_batch_size(api, token).await? as usize;
+    if size == 0 {
+        return Err(anyhow::anyhow!(
+            "batch_transfer is disabled on this wrapper"
+        ));
+    }
+
+    let mut events = Vec::new();
+    for (i, chunk) in recipients.chunks(size).enumerate() {
+        let input = |t: ContractMessageTranscoder<'_>| {
+            // only the selector comes from the metadata, the recipients are encoded as is
+            let mut input = t
+                .encode::<_, String>("batch_transfer", ["[]".to_string()])
+                .unwrap();
+            input.truncate(4);
+            chunk.encode_to(&mut input);
+            input
+        };
+
+        if let Err(data) = token
+            .try_call_or_revert(api, caller.clone(), 0, input)
+            .await?
+        {
+            return Err(anyhow::anyhow!(
+                "chunk {i} reverted with 0x{}, {} recipients paid",
+                hex::encode(data),
+                i * size
+            ));
+        }
+
+        events.extend(token.call(api, caller.clone(), 0, input).await?);
+    }
+
+    Ok(events)
+}
@@ -3,7 +3,8 @@ use parity_scale_codec::{Decode, Encode};
 use sp_core::{hexdisplay::AsBytesRef, U256};
 
 use crate::generic_client::{
-    load_project, Contract, DeployContract, Execution, ReadContract, WriteContract, API,
+    layout::Decoded, load_project, snapshot::Change, solang_storage::SolType, Contract,
+    DeployContract, Execution, ReadContract, WriteContract, API,
 };
 
 #[tokio::test]
@@ -93,6 +94,23 @@ async fn case() -> anyhow::Result<()> {
         }
     }
 
+    // `map` contains a mapping, so it is missing from the metadata layout
+    let storage = contract.solang_storage()?.declare(
+        "map",
+        0_u64,
+        SolType::array(SolType::structure([(
+            "a",
+            SolType::mapping(SolType::uint(256), SolType::uint(256)),
+        )])),
+    );
+
+    let rs = contract
+        .read_slot(&api, &storage.slot("map[1].a[602]")?)
+        .await?;
+    assert_eq!(rs, Some(Decoded::UInt(U256::from(300331_u128))));
+
+    let before = contract.snapshot(&api).await?;
+
     contract
         .call(
             &api,
@@ -105,6 +123,16 @@ async fn case() -> anyhow::Result<()> {
         )
         .await?;
 
+    // rm must clear exactly the one entry and leave nothing behind
+    let after = contract.snapshot(&api).await?;
+    let removed = storage.slot("map[0].a[104]")?;
+    let diff = before.diff(&after);
+    let labels = contract.storage_labels().slot("map[0].a[104]", &removed);
+
+    assert_eq!(diff.len(), 1, "{}", diff.render(&labels));
+    assert!(matches!(diff.get(&removed.key), Some(Change::Removed(_))));
+    assert_eq!(after.len() + 1, before.len());
+
     for i in 0..10 {
         let rs = contract
             .try_call(
@@ -138,5 +166,13 @@ async fn case() -> anyhow::Result<()> {
 
     assert_eq!(rs, 2147483647);
 
+    let rs = contract
+        .read_slot(&api, &storage.slot("map[0].a[104]")?)
+        .await?;
+    assert_eq!(rs, None);
+
+    let rs = contract.read_slot(&api, &storage.slot("number")?).await?;
+    assert_eq!(rs, Some(Decoded::Int(2147483647)));
+
     Ok(())
 }
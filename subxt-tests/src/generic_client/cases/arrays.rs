@@ -1,12 +1,13 @@
 use crate::generic_client::{
-    load_project, Contract, DeployContract, Execution, ReadContract, WriteContract, API,
+    layout::Decoded, load_project, solang_storage::SolType, Contract, DeployContract, Execution,
+    ReadContract, WriteContract, API,
 };
 use contract_transcode::{ContractMessageTranscoder, Value};
 use hex::FromHex;
 use ink_metadata::Selector;
 use parity_scale_codec::{Decode, Encode};
 use rand::{seq::SliceRandom, thread_rng, Rng};
-use sp_core::{crypto::AccountId32, hexdisplay::AsBytesRef};
+use sp_core::{crypto::AccountId32, hexdisplay::AsBytesRef, U256};
 
 #[tokio::test]
 async fn case() -> anyhow::Result<()> {
@@ -105,5 +106,39 @@ async fn case() -> anyhow::Result<()> {
         assert!(has_permission);
     }
 
+    // users is too large for the metadata layout, addressToUser follows right after it
+    let users = SolType::fixed_array(
+        u64::MAX,
+        SolType::structure([
+            ("name", SolType::String),
+            ("addr", SolType::fixed_bytes(32)),
+            ("id", SolType::uint(64)),
+            ("perms", SolType::array(SolType::enumeration())),
+        ]),
+    );
+    let address_to_user_slot = U256::one() + users.slots();
+
+    let storage = contract
+        .solang_storage()?
+        .declare("users", 1_u64, users)
+        .declare(
+            "addressToUser",
+            address_to_user_slot,
+            SolType::mapping(SolType::fixed_bytes(32), SolType::uint(64)),
+        );
+
+    let stored_name = contract
+        .read_slot(&api, &storage.slot(&format!("users[{id}].name"))?)
+        .await?;
+    assert_eq!(stored_name, Some(Decoded::Str(name)));
+
+    let stored_id = contract
+        .read_slot(
+            &api,
+            &storage.slot(&format!("addressToUser[0x{}]", hex::encode(&addr)))?,
+        )
+        .await?;
+    assert_eq!(stored_id, Some(Decoded::UInt(id.into())));
+
     Ok(())
 }
@@ -5,7 +5,7 @@ use crate::generic_client::{
     node::{self, runtime_types::sp_runtime::multiaddress::MultiAddress},
     Contract, WriteContract,
 };
-use crate::utils::free_balance_of;
+use crate::utils::{free_balance_of, BalanceTracker};
 use contract_transcode::ContractMessageTranscoder;
 use hex::FromHex;
 use parity_scale_codec::{Decode, Encode};
@@ -45,8 +45,13 @@ async fn case() -> anyhow::Result<()> {
     let contract_balance = <u128>::decode(&mut rv.as_bytes_ref())?;
     assert!(contract_balance == contract_balance_rpc);
 
-    contract
-        .call(
+    let alice = sp_keyring::AccountKeyring::Alice.to_account_id();
+    let alice_balance = free_balance_of(&api, alice.clone()).await?;
+    let mut tracker =
+        BalanceTracker::native(&api, [alice.clone(), contract.address.clone().unwrap()]).await?;
+
+    let out = contract
+        .call_with_fee(
             &api,
             sp_keyring::AccountKeyring::Alice,
             10_u128.pow(3),
@@ -54,9 +59,28 @@ async fn case() -> anyhow::Result<()> {
         )
         .await?;
 
+    assert_eq!(out.fee.payer, alice);
+    assert!(out.fee.weight > 0);
+
+    let alice_balance_after = free_balance_of(&api, alice.clone()).await?;
+    assert_eq!(
+        alice_balance_after,
+        alice_balance - 10_u128.pow(3) - out.fee.actual_fee
+    );
+
     let contract_balance_after = free_balance_of(&api, contract.address.clone().unwrap()).await?;
     assert_eq!(contract_balance + 10_u128.pow(3), contract_balance_after);
 
+    let report = tracker.report(&api).await?;
+    let contract_delta = report.native(contract.address.as_ref().unwrap()).unwrap();
+    assert_eq!(contract_delta.transferred(), 10_i128.pow(3));
+    assert_eq!(contract_delta.deposit_held, 0);
+
+    // the fee is picked up from the events without being fed in
+    let alice_delta = report.native(&alice).unwrap();
+    assert_eq!(alice_delta.fee, out.fee.actual_fee);
+    assert_eq!(alice_delta.transferred(), -10_i128.pow(3));
+
     let dave = sp_keyring::AccountKeyring::Dave;
     let dave_balance_rpc = free_balance_of(&api, dave.to_account_id()).await?;
 
@@ -1,7 +1,8 @@
-use crate::generic_client::Contract;
+use crate::generic_client::{system::ENV_UTILS, Contract};
+use crate::utils::to_eth_address;
 use contract_transcode::ContractMessageTranscoder;
 use parity_scale_codec::Decode;
-use sp_core::hexdisplay::AsBytesRef;
+use sp_core::{hexdisplay::AsBytesRef, keccak_256, Pair};
 
 #[tokio::test]
 async fn access_env_utils_from_solidity() -> anyhow::Result<()> {
@@ -12,10 +13,10 @@ async fn access_env_utils_from_solidity() -> anyhow::Result<()> {
     )
     .await?;
 
-    // 1A. Deploy the system-contract (env_utils)
+    // 1A. Deploy the system-contract (env_utils) at its well-known address, or upgrade it there
     let mut system_contract = Contract::new("../contracts/env_utils.contract")?;
     system_contract
-        .deploy_as_system_contract(&api, None, 0, &|t: ContractMessageTranscoder<'_>| {
+        .ensure_system_contract(&api, ENV_UTILS, 0, &|t: ContractMessageTranscoder<'_>| {
             t.encode::<_, String>("new", []).unwrap()
         })
         .await?;
@@ -147,5 +148,137 @@ async fn access_env_utils_from_solidity() -> anyhow::Result<()> {
     let res = <(bool, [u8; 33])>::decode(&mut rv.as_bytes_ref())?;
     assert_eq!(res, (true, expected_compressed_pubkey));
 
+    let verify = |message: &'static str, args: [String; 3]| {
+        let contract = &contract;
+        let api = &api;
+
+        async move {
+            let rv = contract
+                .try_call(api, ALICE, 0, move |t: ContractMessageTranscoder<'_>| {
+                    t.encode(message, args.clone()).unwrap()
+                })
+                .await?;
+
+            <bool>::decode(&mut rv.as_bytes_ref()).map_err(anyhow::Error::from)
+        }
+    };
+
+    // 7. Test API -> ecdsa_verify
+    let mut other_pubkey = expected_compressed_pubkey;
+    other_pubkey[32] ^= 1;
+
+    for (pubkey, valid) in [(expected_compressed_pubkey, true), (other_pubkey, false)] {
+        let args = [
+            format!("{:?}", signature),
+            format!("{:?}", msg_hash),
+            format!("{:?}", pubkey),
+        ];
+        assert_eq!(verify("ecdsa_verify", args).await?, valid);
+    }
+
+    // 8. Test API -> blake2_256, keccak_256, sha2_256
+    let input = b"hello laguna";
+    let hashers: [(&str, fn(&[u8]) -> [u8; 32]); 3] = [
+        ("blake2_256", sp_core::blake2_256),
+        ("keccak_256", sp_core::keccak_256),
+        ("sha2_256", sp_core::hashing::sha2_256),
+    ];
+
+    for (api_name, hasher) in hashers {
+        let rv = contract
+            .try_call(&api, ALICE, 0, &|t: ContractMessageTranscoder<'_>| {
+                t.encode(api_name, [format!("0x{}", hex::encode(input))])
+                    .unwrap()
+            })
+            .await?;
+
+        let res = <[u8; 32]>::decode(&mut rv.as_bytes_ref())?;
+        assert_eq!(res, hasher(input), "{api_name}");
+    }
+
+    // 9. Test API -> eth_message_hash, personal_recover (EIP-191)
+    let eth_signer = sp_core::ecdsa::Pair::from_seed(&[9u8; 32]);
+    let eth_address = to_eth_address(eth_signer.public())?.0;
+
+    // what wallets sign, with v in {27, 28}
+    let eth_sign = |hash: [u8; 32]| {
+        let mut sig = eth_signer.sign_prehashed(&hash).0;
+        sig[64] += 27;
+        sig
+    };
+
+    let personal_hash = keccak_256(&[&b"\x19Ethereum Signed Message:\n6"[..], b"laguna"].concat());
+
+    let rv = contract
+        .try_call(&api, ALICE, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode(
+                "eth_message_hash",
+                [format!("0x{}", hex::encode(b"laguna"))],
+            )
+            .unwrap()
+        })
+        .await?;
+    assert_eq!(<[u8; 32]>::decode(&mut rv.as_bytes_ref())?, personal_hash);
+
+    let personal_sig = eth_sign(personal_hash);
+    let rv = contract
+        .try_call(&api, ALICE, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode(
+                "personal_recover",
+                [
+                    format!("0x{}", hex::encode(b"laguna")),
+                    format!("{:?}", personal_sig),
+                ],
+            )
+            .unwrap()
+        })
+        .await?;
+    assert_eq!(
+        <(bool, [u8; 20])>::decode(&mut rv.as_bytes_ref())?,
+        (true, eth_address)
+    );
+
+    // 10. Test API -> eip712_digest, recover_eth_address (EIP-712)
+    let domain_separator = keccak_256(b"domain");
+    let struct_hash = keccak_256(b"struct");
+    let digest = keccak_256(&[&b"\x19\x01"[..], &domain_separator, &struct_hash].concat());
+
+    let rv = contract
+        .try_call(&api, ALICE, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode(
+                "eip712_digest",
+                [
+                    format!("{:?}", domain_separator),
+                    format!("{:?}", struct_hash),
+                ],
+            )
+            .unwrap()
+        })
+        .await?;
+    assert_eq!(<[u8; 32]>::decode(&mut rv.as_bytes_ref())?, digest);
+
+    let typed_sig = eth_sign(digest);
+    let mut broken_sig = typed_sig;
+    broken_sig[..32].copy_from_slice(&[0u8; 32]);
+
+    for (sig, expected) in [
+        (typed_sig, (true, eth_address)),
+        (broken_sig, (false, [0u8; 20])),
+    ] {
+        let rv = contract
+            .try_call(&api, ALICE, 0, &|t: ContractMessageTranscoder<'_>| {
+                t.encode(
+                    "recover_eth_address",
+                    [format!("{:?}", sig), format!("{:?}", digest)],
+                )
+                .unwrap()
+            })
+            .await?;
+        assert_eq!(
+            <(bool, [u8; 20])>::decode(&mut rv.as_bytes_ref())?,
+            expected
+        );
+    }
+
     Ok(())
 }
@@ -0,0 +1,76 @@
+use contract_transcode::ContractMessageTranscoder;
+
+use crate::generic_client::{fuzz::Fuzzer, Contract, API};
+
+async fn deploy(api: &API, path: &'static str) -> anyhow::Result<Contract> {
+    let mut contract = Contract::new(path)?;
+
+    contract
+        .deploy(
+            api,
+            sp_keyring::AccountKeyring::Alice,
+            0,
+            &|t: ContractMessageTranscoder<'_>| t.encode::<_, String>("new", []).unwrap(),
+        )
+        .await?;
+
+    Ok(contract)
+}
+
+#[tokio::test]
+async fn primitives() -> anyhow::Result<()> {
+    let api = API::from_url(
+        std::env::var("ENDPOINT").unwrap_or_else(|_| "ws://127.0.0.1:9944".to_string()),
+    )
+    .await?;
+
+    let contract = deploy(&api, "../contracts/primitives.contract").await?;
+
+    Fuzzer::new(&contract.project, contract.address.clone().unwrap())
+        .runs(64)
+        // integer division and modulo trap on a zero divisor or on MIN / -1
+        .expect_trap(|message, args| {
+            message.starts_with("op_") && matches!(args[0].variant(), Some(3 | 4))
+        })
+        // pow and shifts by huge amounts may run out of gas
+        .expect_failure(|message, args| {
+            message.starts_with("op_") && matches!(args[0].variant(), Some(5 | 6 | 7))
+        })
+        .all_messages(&api, &[])
+        .await
+}
+
+#[tokio::test]
+async fn structs() -> anyhow::Result<()> {
+    let api = API::from_url(
+        std::env::var("ENDPOINT").unwrap_or_else(|_| "ws://127.0.0.1:9944".to_string()),
+    )
+    .await?;
+
+    let contract = deploy(&api, "../contracts/structs.contract").await?;
+
+    Fuzzer::new(&contract.project, contract.address.clone().unwrap())
+        .all_messages(&api, &[])
+        .await
+}
+
+#[tokio::test]
+async fn arrays() -> anyhow::Result<()> {
+    let api = API::from_url(
+        std::env::var("ENDPOINT").unwrap_or_else(|_| "ws://127.0.0.1:9944".to_string()),
+    )
+    .await?;
+
+    let contract = deploy(&api, "../contracts/arrays.contract").await?;
+
+    Fuzzer::new(&contract.project, contract.address.clone().unwrap())
+        // these assert that the user exists, the others index `users[id]` out of bounds on the
+        // largest id
+        .expect_trap(|message, args| {
+            ["getUserById", "getUserByAddress", "hasPermission"].contains(&message)
+                || ["addUser", "userExists", "removeUser"].contains(&message)
+                    && args[0].is_unsigned_max()
+        })
+        .all_messages(&api, &[])
+        .await
+}
@@ -10,13 +10,18 @@ mod env_utils;
 mod events;
 mod external_call;
 mod flipper;
+mod fuzz;
 mod issue666;
 mod msg_sender;
+mod native_token_escrow;
+mod native_token_factory;
 mod native_token_wrapper;
 mod primitives;
 mod randomizer;
 mod store;
 mod structs;
+mod system_contracts;
 mod uniswapv2_erc20;
 mod uniswapv2_factory;
 mod uniswapv2_pair;
+mod uniswapv2_pair_invariants;
@@ -0,0 +1,247 @@
+use contract_transcode::ContractMessageTranscoder;
+use parity_scale_codec::Decode;
+use sp_core::{crypto::AccountId32, Pair, U256};
+use sp_keyring::AccountKeyring;
+
+use super::native_token_wrapper::WrapperError;
+use crate::generic_client::{fixture::fresh_account, Contract};
+use crate::utils::free_balance_of;
+
+/// Mirrors `native_token_escrow::Error`
+#[derive(Decode, Debug, PartialEq, Eq)]
+enum EscrowError {
+    UnknownDeal,
+    NotAllowed,
+    BalanceOverflow,
+    Token(WrapperError),
+}
+
+#[tokio::test]
+async fn escrow_through_native_token_ref() -> anyhow::Result<()> {
+    const ALICE: AccountKeyring = AccountKeyring::Alice;
+    const DAVE: AccountKeyring = AccountKeyring::Dave;
+
+    let api = crate::API::from_url(
+        std::env::var("ENDPOINT").unwrap_or_else(|_| "ws://127.0.0.1:9944".to_string()),
+    )
+    .await?;
+
+    // 1. The escrow composes the wrapper of the native token 0
+    let mut token = Contract::new("../contracts/native_token_wrapper.contract")?;
+    token
+        .deploy_as_system_contract(&api, None, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>("create_wrapper_token", [format!("{}", 0_u32)])
+                .unwrap()
+        })
+        .await?;
+    let token_addr = token.address().unwrap().clone();
+
+    let mut escrow = Contract::new("../contracts/native_token_escrow.contract")?;
+    escrow
+        .deploy(&api, ALICE, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode("new", [format!("{:?}", token_addr)]).unwrap()
+        })
+        .await?;
+    let escrow_addr = escrow.address().unwrap().clone();
+
+    // payer and payee are fresh so their balances only move through this test
+    let payer = fresh_account(&api, 10u128.pow(19)).await?;
+    let payee = fresh_account(&api, 10u128.pow(18)).await?;
+    let payer_id: AccountId32 = payer.public().into();
+    let payee_id: AccountId32 = payee.public().into();
+
+    let value = U256::exp10(18);
+
+    // 2. The payer approves the escrow on the wrapper, then deposits
+    token
+        .call(&api, payer.clone(), 0, &|t: ContractMessageTranscoder<
+            '_,
+        >| {
+            t.encode::<_, String>(
+                "approve",
+                [format!("{:?}", escrow_addr), format!("{:?}", value.0)],
+            )
+            .unwrap()
+        })
+        .await?;
+
+    let deposit = |value: U256| {
+        let payee_id = payee_id.clone();
+
+        move |t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>(
+                "deposit",
+                [
+                    format!("{:?}", payee_id),
+                    format!("{:?}", ALICE.to_account_id()),
+                    format!("{:?}", value.0),
+                ],
+            )
+            .unwrap()
+        }
+    };
+
+    let escrow_before = free_balance_of(&api, escrow_addr.clone()).await?;
+    let events = escrow.call(&api, payer.clone(), 0, deposit(value)).await?;
+
+    // Deposited { id, payer, payee, value }, next to the wrapper's Transfer
+    let deposited = events
+        .iter()
+        .find(|e| e.contract == escrow_addr)
+        .expect("the escrow emits Deposited");
+    let (_, id, from, to, deposited_value) =
+        <(u8, u32, AccountId32, AccountId32, U256)>::decode(&mut &deposited.data[..])?;
+    assert_eq!(
+        (from, to, deposited_value),
+        (payer_id.clone(), payee_id.clone(), value)
+    );
+    assert!(events.iter().any(|e| e.contract == token_addr));
+
+    assert_eq!(
+        free_balance_of(&api, escrow_addr.clone()).await?,
+        escrow_before + value.as_u128()
+    );
+
+    let rv = escrow
+        .try_call(&api, ALICE, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode("deal", [id.to_string()]).unwrap()
+        })
+        .await?;
+    assert_eq!(
+        <Option<(AccountId32, AccountId32, AccountId32, u128)>>::decode(&mut &rv[..])?,
+        Some((
+            payer_id.clone(),
+            payee_id.clone(),
+            ALICE.to_account_id(),
+            value.as_u128()
+        ))
+    );
+
+    // 3. The allowance is spent, a second deposit fails with the reason of the wrapper
+    let rv = escrow
+        .try_call_or_revert(&api, payer.clone(), 0, deposit(value))
+        .await?;
+    assert_eq!(
+        <Result<u32, EscrowError>>::decode(&mut &rv.unwrap_err()[..])?,
+        Err(EscrowError::Token(WrapperError::InsufficientAllowance))
+    );
+
+    // 4. Neither outsiders nor the payee can release the deal
+    let release = |id: u32| {
+        move |t: ContractMessageTranscoder<'_>| t.encode("release", [id.to_string()]).unwrap()
+    };
+
+    for caller in [DAVE.pair(), payee.clone()] {
+        let rv = escrow
+            .try_call_or_revert(&api, caller, 0, release(id))
+            .await?;
+        assert_eq!(
+            <Result<(), EscrowError>>::decode(&mut &rv.unwrap_err()[..])?,
+            Err(EscrowError::NotAllowed)
+        );
+    }
+
+    // 5. The arbiter releases it, the escrow pays the payee through the wrapper
+    let payee_before = free_balance_of(&api, payee_id.clone()).await?;
+    escrow.call(&api, ALICE, 0, release(id)).await?;
+
+    assert_eq!(
+        free_balance_of(&api, payee_id.clone()).await?,
+        payee_before + value.as_u128()
+    );
+    assert_eq!(
+        free_balance_of(&api, escrow_addr.clone()).await?,
+        escrow_before
+    );
+
+    // 6. A settled deal is gone
+    let rv = escrow
+        .try_call_or_revert(&api, ALICE, 0, release(id))
+        .await?;
+    assert_eq!(
+        <Result<(), EscrowError>>::decode(&mut &rv.unwrap_err()[..])?,
+        Err(EscrowError::UnknownDeal)
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn escrow_refunds_the_payer() -> anyhow::Result<()> {
+    const ALICE: AccountKeyring = AccountKeyring::Alice;
+
+    let api = crate::API::from_url(
+        std::env::var("ENDPOINT").unwrap_or_else(|_| "ws://127.0.0.1:9944".to_string()),
+    )
+    .await?;
+
+    let mut token = Contract::new("../contracts/native_token_wrapper.contract")?;
+    token
+        .deploy_as_system_contract(&api, None, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>("create_wrapper_token", [format!("{}", 0_u32)])
+                .unwrap()
+        })
+        .await?;
+    let token_addr = token.address().unwrap().clone();
+
+    let mut escrow = Contract::new("../contracts/native_token_escrow.contract")?;
+    escrow
+        .deploy(&api, ALICE, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode("new", [format!("{:?}", token_addr)]).unwrap()
+        })
+        .await?;
+    let escrow_addr = escrow.address().unwrap().clone();
+
+    let payer = fresh_account(&api, 10u128.pow(19)).await?;
+    let payee = fresh_account(&api, 10u128.pow(18)).await?;
+    let payee_id: AccountId32 = payee.public().into();
+
+    let value = U256::exp10(18);
+
+    token
+        .call(&api, payer.clone(), 0, &|t: ContractMessageTranscoder<
+            '_,
+        >| {
+            t.encode::<_, String>(
+                "approve",
+                [format!("{:?}", escrow_addr), format!("{:?}", value.0)],
+            )
+            .unwrap()
+        })
+        .await?;
+
+    escrow
+        .call(&api, payer.clone(), 0, &|t: ContractMessageTranscoder<
+            '_,
+        >| {
+            t.encode::<_, String>(
+                "deposit",
+                [
+                    format!("{:?}", payee_id),
+                    format!("{:?}", ALICE.to_account_id()),
+                    format!("{:?}", value.0),
+                ],
+            )
+            .unwrap()
+        })
+        .await?;
+
+    // the payee refunds, the payer gets the deposit back and only paid the fees
+    let payer_balance = || {
+        let api = &api;
+        let payer = payer.public();
+
+        async move { free_balance_of(api, payer.into()).await }
+    };
+    let payer_before = payer_balance().await?;
+
+    escrow
+        .call(&api, payee, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode("refund", ["0"]).unwrap()
+        })
+        .await?;
+
+    assert_eq!(payer_balance().await?, payer_before + value.as_u128());
+
+    Ok(())
+}
@@ -0,0 +1,117 @@
+use contract_transcode::ContractMessageTranscoder;
+use parity_scale_codec::Decode;
+use sp_core::{blake2_256, crypto::AccountId32};
+use sp_keyring::AccountKeyring;
+
+use crate::generic_client::{node, Contract};
+
+#[tokio::test]
+async fn wraps_native_tokens_lazily() -> anyhow::Result<()> {
+    const ALICE: AccountKeyring = AccountKeyring::Alice;
+
+    let api = crate::API::from_url(
+        std::env::var("ENDPOINT").unwrap_or_else(|_| "ws://127.0.0.1:9944".to_string()),
+    )
+    .await?;
+
+    // 1. Upload the wrapper code and deploy the factory as a system-contract
+    let wrapper = Contract::new("../contracts/native_token_wrapper.contract")?;
+    wrapper.upload_code(&api, ALICE).await?;
+    let wrapper_code_hash = blake2_256(&wrapper.blob);
+
+    let mut factory = Contract::new("../contracts/native_token_factory.contract")?;
+    factory
+        .deploy_as_system_contract(&api, None, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode("new", [format!("0x{}", hex::encode(wrapper_code_hash))])
+                .unwrap()
+        })
+        .await?;
+
+    // 2. No native token is wrapped yet
+    let wrapped_tokens = || {
+        let factory = &factory;
+        let api = &api;
+
+        async move {
+            factory
+                .try_call(api, ALICE, 0, &|t: ContractMessageTranscoder<'_>| {
+                    t.encode::<_, String>("wrapped_tokens", []).unwrap()
+                })
+                .await
+                .and_then(|v| <Vec<u32>>::decode(&mut &v[..]).map_err(Into::into))
+        }
+    };
+    assert!(wrapped_tokens().await?.is_empty());
+
+    let get_wrapper = |token_id: u32| {
+        let factory = &factory;
+        let api = &api;
+
+        async move {
+            factory
+                .try_call(api, ALICE, 0, &|t: ContractMessageTranscoder<'_>| {
+                    t.encode("get_wrapper", [token_id.to_string()]).unwrap()
+                })
+                .await
+                .and_then(|v| <(bool, AccountId32)>::decode(&mut &v[..]).map_err(Into::into))
+        }
+    };
+
+    assert_eq!(
+        get_wrapper(0).await?,
+        (false, AccountId32::from([0_u8; 32]))
+    );
+
+    // 3. The first wrap instantiates the wrapper and records it in the registry
+    let events = factory
+        .call(&api, ALICE, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode("wrap", ["0"]).unwrap()
+        })
+        .await?;
+    assert_eq!(events.len(), 1);
+    assert_eq!(wrapped_tokens().await?, vec![0]);
+
+    let (found, address) = get_wrapper(0).await?;
+    assert!(found);
+
+    let info = api
+        .storage()
+        .fetch(
+            &node::storage().contracts().contract_info_of(&address),
+            None,
+        )
+        .await?
+        .expect("the wrapper is a contract");
+    assert_eq!(info.code_hash.0, wrapper_code_hash);
+
+    // 4. The wrapper works as the ERC-20 of token 0
+    let token = wrapper.from_addr(address.clone())?;
+    let decimals = token
+        .try_call(&api, ALICE, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>("decimals", []).unwrap()
+        })
+        .await
+        .and_then(|v| <u8>::decode(&mut &v[..]).map_err(Into::into))?;
+    assert!(decimals > 0);
+
+    // 5. Later wraps reuse it
+    let events = factory
+        .call(&api, ALICE, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode("wrap", ["0"]).unwrap()
+        })
+        .await?;
+    assert!(events.is_empty());
+    assert_eq!(get_wrapper(0).await?, (true, address));
+    assert_eq!(wrapped_tokens().await?, vec![0]);
+
+    // 6. Unknown tokens can't be wrapped
+    assert!(factory
+        .call(&api, ALICE, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode("wrap", [u32::MAX.to_string()]).unwrap()
+        })
+        .await
+        .is_err());
+    assert_eq!(wrapped_tokens().await?, vec![0]);
+
+    Ok(())
+}
@@ -1,16 +1,20 @@
 use crate::generic_client::{
+    batch::{batch_transfer, max_batch_size},
+    fixture::fresh_account,
     node::{
         self,
         runtime_types::primitives::currency::{CurrencyId, TokenId},
     },
+    system::ENV_UTILS,
     Contract,
 };
-use crate::utils::free_balance_of;
+use crate::utils::{default_account_id, free_balance_of, to_eth_address};
 
 use contract_transcode::ContractMessageTranscoder;
 use parity_scale_codec::{Decode, Encode};
-use sp_core::{hexdisplay::AsBytesRef, U256};
+use sp_core::{crypto::AccountId32, ecdsa, hexdisplay::AsBytesRef, keccak_256, Pair, U256};
 use sp_keyring::AccountKeyring;
+use subxt::rpc::{rpc_params, ClientT};
 
 #[tokio::test]
 async fn ink_multilayer_erc20() -> anyhow::Result<()> {
@@ -32,6 +36,10 @@ async fn ink_multilayer_erc20() -> anyhow::Result<()> {
         })
         .await?;
 
+    // 1B. The wrapped token id is readable straight from the storage layout
+    let token_id = contract.read_field::<u32>(&api, "token_id").await?;
+    assert_eq!(token_id, Some(0));
+
     // 2. Test name()
     let rv = contract
         .try_call(&api, ALICE, 0, &|t: ContractMessageTranscoder<'_>| {
@@ -157,6 +165,16 @@ async fn ink_multilayer_erc20() -> anyhow::Result<()> {
     let allowance = <U256>::decode(&mut rv.as_bytes_ref())?;
     assert_eq!(allowance, allow_value);
 
+    let stored_allowance = contract
+        .read_mapping::<_, [u64; 4]>(
+            &api,
+            "allowances",
+            &(BOB.to_account_id(), ALICE.to_account_id()),
+        )
+        .await?
+        .map(U256);
+    assert_eq!(stored_allowance, Some(allow_value));
+
     // 10. Test transfer_from()
     // @dev: ALICE transfers 0.1 LAGUNA from BOB to EVE
     let transfer_value = U256::exp10(18 - 1);
@@ -187,5 +205,591 @@ async fn ink_multilayer_erc20() -> anyhow::Result<()> {
     assert_eq!(eve_balance_after, eve_balance_before + transfer_value);
     assert_eq!(updated_allowance, allowance - transfer_value);
 
+    let stored_allowance = contract
+        .read_mapping::<_, [u64; 4]>(
+            &api,
+            "allowances",
+            &(BOB.to_account_id(), ALICE.to_account_id()),
+        )
+        .await?
+        .map(U256);
+    assert_eq!(stored_allowance, Some(updated_allowance));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn ink_multilayer_erc20_permit() -> anyhow::Result<()> {
+    const ALICE: AccountKeyring = AccountKeyring::Alice;
+    const BOB: AccountKeyring = AccountKeyring::Bob;
+
+    let api = crate::API::from_url(
+        std::env::var("ENDPOINT").unwrap_or_else(|_| "ws://127.0.0.1:9944".to_string()),
+    )
+    .await?;
+
+    // 1. Signatures are recovered through env_utils at its well-known address
+    let mut env_utils = Contract::new("../contracts/env_utils.contract")?;
+    env_utils
+        .ensure_system_contract(&api, ENV_UTILS, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>("new", []).unwrap()
+        })
+        .await?;
+
+    // 2. Permits are bound to the chain wallets sign for, given at instantiation
+    let chain_id: U256 = api
+        .rpc()
+        .client
+        .request("eth_chainId", rpc_params![])
+        .await?;
+
+    let mut contract = Contract::new("../contracts/native_token_wrapper.contract")?;
+    contract
+        .deploy_as_system_contract(&api, None, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>(
+                "create_wrapper_token_for_chain",
+                [
+                    format!("{}", 0_u32),
+                    format!("{}", 64_u32),
+                    format!("{}", chain_id.as_u64()),
+                ],
+            )
+            .unwrap()
+        })
+        .await?;
+
+    // 3. The domain separator matches the one signers compute off-chain
+    let rv = contract
+        .try_call(&api, ALICE, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>("domain_separator", []).unwrap()
+        })
+        .await?;
+    let domain_separator = <[u8; 32]>::decode(&mut rv.as_bytes_ref())?;
+
+    let mut chain_word = [0u8; 32];
+    chain_id.to_big_endian(&mut chain_word);
+
+    let expected = keccak_256(
+        &[
+            keccak_256(b"EIP712Domain(string name,string version,uint256 chainId,bytes32 salt)"),
+            keccak_256(b"LAGUNA"),
+            keccak_256(b"1"),
+            chain_word,
+            contract.address().unwrap().clone().into(),
+        ]
+        .concat(),
+    );
+    assert_eq!(domain_separator, expected);
+
+    // 4. A fresh ethereum owner has not used any permit yet
+    let (signer, _) = ecdsa::Pair::generate();
+    let owner = to_eth_address(signer.public())?.0;
+
+    let nonce = || {
+        let api = &api;
+        let contract = &contract;
+
+        async move {
+            contract
+                .try_call(api, ALICE, 0, &|t: ContractMessageTranscoder<'_>| {
+                    t.encode::<_, String>("nonces", [format!("{:?}", owner)])
+                        .unwrap()
+                })
+                .await
+                .and_then(|v| <U256>::decode(&mut &v[..]).map_err(Into::into))
+        }
+    };
+    assert_eq!(nonce().await?, U256::zero());
+
+    // 5. BOB submits the permit signed by the owner, which lets BOB spend 1 LAGUNA of the
+    //    owner's default account
+    let value = U256::exp10(18);
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?;
+    let deadline = U256::from(now.as_secs() + 3600);
+
+    let spender: [u8; 32] = BOB.to_account_id().into();
+    let sign = |nonce: U256, deadline: U256| {
+        let word = |v: U256| {
+            let mut buf = [0u8; 32];
+            v.to_big_endian(&mut buf);
+            buf
+        };
+
+        let struct_hash = keccak_256(
+            &[
+                keccak_256(
+                    b"Permit(bytes20 owner,bytes32 spender,uint256 value,uint256 nonce,uint256 deadline)",
+                )
+                .as_slice(),
+                // bytes20 is left-aligned
+                &owner,
+                &[0u8; 12],
+                &spender,
+                &word(value),
+                &word(nonce),
+                &word(deadline),
+            ]
+            .concat(),
+        );
+        let digest = keccak_256(&[&b"\x19\x01"[..], &domain_separator, &struct_hash].concat());
+
+        // r ++ s ++ recovery id
+        let sig = signer.sign_prehashed(&digest).0;
+        (
+            sig[64] + 27,
+            <[u8; 32]>::try_from(&sig[..32]).unwrap(),
+            <[u8; 32]>::try_from(&sig[32..64]).unwrap(),
+        )
+    };
+
+    let sel_permit = |deadline: U256, (v, r, s): (u8, [u8; 32], [u8; 32])| {
+        move |t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>(
+                "permit",
+                [
+                    format!("{:?}", owner),
+                    format!("{:?}", BOB.to_account_id()),
+                    format!("{:?}", value.0),
+                    format!("{:?}", deadline.0),
+                    format!("{}", v),
+                    format!("{:?}", r),
+                    format!("{:?}", s),
+                ],
+            )
+            .unwrap()
+        }
+    };
+
+    let signature = sign(U256::zero(), deadline);
+    let events = contract
+        .call(&api, BOB, 0, sel_permit(deadline, signature))
+        .await?;
+
+    // Approval { owner, spender, value }, the owner being the default account of the signer
+    let owner_account = default_account_id(owner.into());
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        <(u8, AccountId32, AccountId32, U256)>::decode(&mut &events[0].data[..])?,
+        (1, owner_account.clone(), BOB.to_account_id(), value)
+    );
+
+    let rv = contract
+        .try_call(&api, ALICE, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>(
+                "allowance",
+                [
+                    format!("{:?}", owner_account),
+                    format!("{:?}", BOB.to_account_id()),
+                ],
+            )
+            .unwrap()
+        })
+        .await?;
+    assert_eq!(<U256>::decode(&mut rv.as_bytes_ref())?, value);
+    assert_eq!(nonce().await?, U256::one());
+
+    // 6. The same signature can't be replayed, the nonce it was signed with is used up
+    let rv = contract
+        .try_call_or_revert(&api, BOB, 0, sel_permit(deadline, signature))
+        .await?;
+    assert_eq!(revert_reason(&rv.unwrap_err())?, "InvalidSignature");
+
+    // 7. Expired permits are rejected even with a valid signature
+    let expired = U256::one();
+    let rv = contract
+        .try_call_or_revert(
+            &api,
+            BOB,
+            0,
+            sel_permit(expired, sign(U256::one(), expired)),
+        )
+        .await?;
+    assert_eq!(revert_reason(&rv.unwrap_err())?, "PermitExpired");
+    assert_eq!(nonce().await?, U256::one());
+
+    Ok(())
+}
+
+/// Needs a runtime providing extensions 210 and 211, and the wrapper built with `mint-burn`
+#[cfg(feature = "mint-burn")]
+#[tokio::test]
+async fn ink_multilayer_erc20_mint_burn() -> anyhow::Result<()> {
+    // ALICE is the sudo key of the dev chain, which holds the minter and burner roles
+    const ALICE: AccountKeyring = AccountKeyring::Alice;
+    const BOB: AccountKeyring = AccountKeyring::Bob;
+    const EVE: AccountKeyring = AccountKeyring::Eve;
+
+    let api = crate::API::from_url(
+        std::env::var("ENDPOINT").unwrap_or_else(|_| "ws://127.0.0.1:9944".to_string()),
+    )
+    .await?;
+
+    let mut contract = Contract::new("../contracts/native_token_wrapper.contract")?;
+    contract
+        .deploy_as_system_contract(&api, None, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>("create_wrapper_token", [format!("{}", 0_u32)])
+                .unwrap()
+        })
+        .await?;
+
+    let total_supply = || {
+        let api = &api;
+        let contract = &contract;
+
+        async move {
+            contract
+                .try_call(api, ALICE, 0, &|t: ContractMessageTranscoder<'_>| {
+                    t.encode::<_, String>("total_supply", []).unwrap()
+                })
+                .await
+                .and_then(|v| <U256>::decode(&mut &v[..]).map_err(Into::into))
+        }
+    };
+
+    let value = U256::exp10(18);
+    let sel = |message: &'static str| {
+        move |t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>(
+                message,
+                [
+                    format!("{:?}", EVE.to_account_id()),
+                    format!("{:?}", value.0),
+                ],
+            )
+            .unwrap()
+        }
+    };
+
+    // 1. Callers without a role are refused by the runtime
+    for message in ["mint", "burn"] {
+        let rv = contract.try_call(&api, BOB, 0, sel(message)).await?;
+        assert!(!<bool>::decode(&mut rv.as_bytes_ref())?);
+    }
+
+    // 2. mint() issues new tokens, emitting a Transfer from None
+    let supply_before = total_supply().await?;
+    let eve_before: U256 = free_balance_of(&api, EVE.to_account_id()).await?.into();
+
+    let events = contract.call(&api, ALICE, 0, sel("mint")).await?;
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        <(u8, Option<AccountId32>, Option<AccountId32>, U256)>::decode(&mut &events[0].data[..])?,
+        (0, None, Some(EVE.to_account_id()), value)
+    );
+
+    let eve_after: U256 = free_balance_of(&api, EVE.to_account_id()).await?.into();
+    assert_eq!(eve_after, eve_before + value);
+    assert_eq!(total_supply().await?, supply_before + value);
+
+    // 3. burn() destroys them again, emitting a Transfer to None
+    let events = contract.call(&api, ALICE, 0, sel("burn")).await?;
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        <(u8, Option<AccountId32>, Option<AccountId32>, U256)>::decode(&mut &events[0].data[..])?,
+        (0, Some(EVE.to_account_id()), None, value)
+    );
+
+    let eve_after: U256 = free_balance_of(&api, EVE.to_account_id()).await?.into();
+    assert_eq!(eve_after, eve_before);
+    assert_eq!(total_supply().await?, supply_before);
+
+    Ok(())
+}
+
+/// Mirrors `laguna_env::ExtensionError`
+#[derive(Decode, Debug, PartialEq, Eq)]
+pub(super) enum ExtensionError {
+    InvalidTokenId,
+    InsufficientBalance,
+    AccessDenied,
+    UnknownStatusCode,
+    InvalidScaleEncoding,
+    BelowExistentialDeposit,
+    AccountFrozen,
+    ArithmeticOverflow,
+    NotTransferable,
+}
+
+/// Mirrors `native_token_wrapper::Error`
+#[derive(Decode, Debug, PartialEq, Eq)]
+pub(super) enum WrapperError {
+    InsufficientAllowance,
+    AllowanceOverflow,
+    BalanceOverflow,
+    Extension(ExtensionError),
+    BatchTooLarge,
+    UnknownChain,
+    PermitExpired,
+    InvalidSignature,
+}
+
+/// Reason of a `try_*` message, `batch_transfer` or `permit`, which revert with solidity's
+/// `Error(string)`
+fn revert_reason(data: &[u8]) -> anyhow::Result<String> {
+    match data {
+        [0x08, 0xc3, 0x79, 0xa0, reason @ ..] => {
+            String::decode(&mut &reason[..]).map_err(Into::into)
+        }
+        _ => Err(anyhow::anyhow!(
+            "0x{} is not an Error(string)",
+            hex::encode(data)
+        )),
+    }
+}
+
+#[tokio::test]
+async fn ink_multilayer_erc20_revert_reasons() -> anyhow::Result<()> {
+    const ALICE: AccountKeyring = AccountKeyring::Alice;
+    const BOB: AccountKeyring = AccountKeyring::Bob;
+
+    let api = crate::API::from_url(
+        std::env::var("ENDPOINT").unwrap_or_else(|_| "ws://127.0.0.1:9944".to_string()),
+    )
+    .await?;
+
+    let mut contract = Contract::new("../contracts/native_token_wrapper.contract")?;
+    contract
+        .deploy_as_system_contract(&api, None, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>("create_wrapper_token", [format!("{}", 0_u32)])
+                .unwrap()
+        })
+        .await?;
+
+    let reason_of = |message: &'static str, args: Vec<String>| {
+        let api = &api;
+        let contract = &contract;
+
+        async move {
+            let rv = contract
+                .try_call_or_revert(api, BOB, 0, move |t: ContractMessageTranscoder<'_>| {
+                    t.encode(message, args.clone()).unwrap()
+                })
+                .await?;
+
+            match rv {
+                Ok(_) => Err(anyhow::anyhow!("{message} didn't revert")),
+                Err(data) => revert_reason(&data),
+            }
+        }
+    };
+
+    let bob_balance: U256 = free_balance_of(&api, BOB.to_account_id()).await?.into();
+
+    // 1. Moving more than the balance is refused by the runtime
+    assert_eq!(
+        reason_of(
+            "try_transfer",
+            vec![
+                format!("{:?}", ALICE.to_account_id()),
+                format!("{:?}", (bob_balance + 1).0),
+            ],
+        )
+        .await?,
+        "InsufficientBalance"
+    );
+
+    // 2. Values beyond u128 never reach the runtime
+    assert_eq!(
+        reason_of(
+            "try_transfer",
+            vec![
+                format!("{:?}", ALICE.to_account_id()),
+                format!("{:?}", U256::MAX.0),
+            ],
+        )
+        .await?,
+        "BalanceOverflow"
+    );
+
+    // 3. Allowances are checked by the wrapper itself
+    assert_eq!(
+        reason_of(
+            "try_transfer_from",
+            vec![
+                format!("{:?}", ALICE.to_account_id()),
+                format!("{:?}", BOB.to_account_id()),
+                format!("{:?}", U256::one().0),
+            ],
+        )
+        .await?,
+        "InsufficientAllowance"
+    );
+
+    // 4. BOB holds no minter role, and wrappers built without `mint-burn` can't mint at all
+    let expected = if cfg!(feature = "mint-burn") {
+        "AccessDenied"
+    } else {
+        "Unsupported"
+    };
+    assert_eq!(
+        reason_of(
+            "try_mint",
+            vec![
+                format!("{:?}", BOB.to_account_id()),
+                format!("{:?}", U256::one().0),
+            ],
+        )
+        .await?,
+        expected
+    );
+
+    // 5. The plain messages still report failures as `false`
+    let rv = contract
+        .try_call(&api, BOB, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>(
+                "transfer",
+                [
+                    format!("{:?}", ALICE.to_account_id()),
+                    format!("{:?}", U256::MAX.0),
+                ],
+            )
+            .unwrap()
+        })
+        .await?;
+    assert!(!<bool>::decode(&mut rv.as_bytes_ref())?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn ink_multilayer_erc20_solang_caller() -> anyhow::Result<()> {
+    const ALICE: AccountKeyring = AccountKeyring::Alice;
+    const BOB: AccountKeyring = AccountKeyring::Bob;
+
+    let api = crate::API::from_url(
+        std::env::var("ENDPOINT").unwrap_or_else(|_| "ws://127.0.0.1:9944".to_string()),
+    )
+    .await?;
+
+    let mut token = Contract::new("../contracts/native_token_wrapper.contract")?;
+    token
+        .deploy_as_system_contract(&api, None, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>("create_wrapper_token", [format!("{}", 0_u32)])
+                .unwrap()
+        })
+        .await?;
+    let token_addr = token.address().unwrap().clone();
+
+    // 1. A solidity contract holding a little of the token, through its endowment
+    let mut caller = Contract::new("../contracts/native_token_caller.contract")?;
+    caller
+        .deploy(
+            &api,
+            ALICE,
+            10_u128.pow(16),
+            &|t: ContractMessageTranscoder<'_>| {
+                t.encode::<_, String>("new", [format!("0x{}", hex::encode(&token_addr))])
+                    .unwrap()
+            },
+        )
+        .await?;
+
+    // solang's `catch Error(string memory reason)` hands the reason back, "" if nothing reverted
+    let reason_of = |message: &'static str, addresses: Vec<AccountId32>, value: U256| {
+        let api = &api;
+        let caller = &caller;
+
+        async move {
+            let rv = caller
+                .try_call(api, ALICE, 0, move |t: ContractMessageTranscoder<'_>| {
+                    let args = addresses
+                        .iter()
+                        .map(|a| format!("0x{}", hex::encode(a)))
+                        .collect::<Vec<_>>();
+                    let mut input = t.encode(message, args).unwrap();
+                    value.encode_to(&mut input);
+                    input
+                })
+                .await?;
+
+            <String>::decode(&mut rv.as_bytes_ref()).map_err(anyhow::Error::from)
+        }
+    };
+
+    // 2. The reasons of the wrapper reach the solidity caller
+    assert_eq!(
+        reason_of("try_transfer", vec![BOB.to_account_id()], U256::exp10(30)).await?,
+        "InsufficientBalance"
+    );
+    assert_eq!(
+        reason_of("try_transfer", vec![BOB.to_account_id()], U256::MAX).await?,
+        "BalanceOverflow"
+    );
+    assert_eq!(
+        reason_of(
+            "try_transfer_from",
+            vec![ALICE.to_account_id(), BOB.to_account_id()],
+            U256::one()
+        )
+        .await?,
+        "InsufficientAllowance"
+    );
+
+    // 3. Nothing to catch when the transfer goes through
+    assert_eq!(
+        reason_of("try_transfer", vec![BOB.to_account_id()], U256::one()).await?,
+        ""
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn ink_multilayer_erc20_batch_transfer() -> anyhow::Result<()> {
+    let api = crate::API::from_url(
+        std::env::var("ENDPOINT").unwrap_or_else(|_| "ws://127.0.0.1:9944".to_string()),
+    )
+    .await?;
+
+    // 1. A wrapper taking at most 2 recipients per batch
+    let mut contract = Contract::new("../contracts/native_token_wrapper.contract")?;
+    contract
+        .deploy_as_system_contract(&api, None, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>("create_wrapper_token_with_batch_size", ["0", "2"])
+                .unwrap()
+        })
+        .await?;
+    assert_eq!(max_batch_size(&api, &contract).await?, 2);
+
+    let payer = fresh_account(&api, 10u128.pow(20)).await?;
+    let recipients = (0..5)
+        .map(|i| {
+            let (pair, _) = sp_core::sr25519::Pair::generate();
+            (pair.public().into(), U256::exp10(18) * (i + 1))
+        })
+        .collect::<Vec<(AccountId32, U256)>>();
+
+    // 2. Oversized batches are refused as a whole
+    let rv = contract
+        .try_call_or_revert(
+            &api,
+            payer.clone(),
+            0,
+            |t: ContractMessageTranscoder<'_>| {
+                let mut input = t
+                    .encode::<_, String>("batch_transfer", ["[]".to_string()])
+                    .unwrap();
+                input.truncate(4);
+                recipients.encode_to(&mut input);
+                input
+            },
+        )
+        .await?;
+    assert_eq!(revert_reason(&rv.unwrap_err())?, "BatchTooLarge");
+
+    // 3. The client helper spreads them over 3 calls, one Transfer per recipient
+    let events = batch_transfer(&api, &contract, payer.clone(), &recipients).await?;
+    assert_eq!(events.len(), recipients.len());
+
+    for (event, (to, value)) in events.iter().zip(&recipients) {
+        let (_, from, event_to, event_value) =
+            <(u8, Option<AccountId32>, Option<AccountId32>, U256)>::decode(&mut &event.data[..])?;
+        assert_eq!(from, Some(payer.public().into()));
+        assert_eq!((event_to.as_ref(), event_value), (Some(to), *value));
+
+        let balance: U256 = free_balance_of(&api, to.clone()).await?.into();
+        assert_eq!(balance, *value);
+    }
+
     Ok(())
 }
@@ -5,7 +5,8 @@ use rand::Rng;
 use sp_core::{hexdisplay::AsBytesRef, keccak_256, U256};
 
 use crate::generic_client::{
-    load_project, DeployContract, Execution, ReadContract, WriteContract, API,
+    layout::Decoded, load_project, Contract, DeployContract, Execution, ReadContract,
+    WriteContract, API,
 };
 
 #[tokio::test]
@@ -145,6 +146,30 @@ async fn case() -> anyhow::Result<()> {
         )
     );
 
+    // the same values, read straight from their solang storage slots
+    let reader = Contract::new("../contracts/store.contract")?
+        .from_addr(contract.contract_address.clone())?;
+    let storage = reader.solang_storage()?;
+
+    assert_eq!(
+        reader.read_slot(&api, &storage.slot("u64")?).await?,
+        Some(Decoded::UInt(u64::MAX.into()))
+    );
+    assert_eq!(
+        reader.read_slot(&api, &storage.slot("i16")?).await?,
+        Some(Decoded::Int(32766))
+    );
+    assert_eq!(
+        reader.read_slot(&api, &storage.slot("str")?).await?,
+        Some(Decoded::Str(
+            "the course of true love never did run smooth".into()
+        ))
+    );
+    assert_eq!(
+        reader.read_slot(&api, &storage.slot("bs")?).await?,
+        Some(Decoded::Bytes(hex::decode("b00b1e")?))
+    );
+
     let selector = transcoder.encode::<_, String>("do_ops", [])?;
 
     WriteContract {
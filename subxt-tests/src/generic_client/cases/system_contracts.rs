@@ -0,0 +1,144 @@
+use contract_transcode::ContractMessageTranscoder;
+use parity_scale_codec::Decode;
+use sp_core::crypto::AccountId32;
+
+use crate::generic_client::{
+    system::{is_system_contract, system_contracts},
+    Contract, API,
+};
+
+fn flipper_new(init: &'static str) -> impl Fn(ContractMessageTranscoder<'_>) -> Vec<u8> {
+    move |t| t.encode("new", [init]).unwrap()
+}
+
+async fn get(api: &API, contract: &Contract) -> anyhow::Result<bool> {
+    contract
+        .try_call(
+            api,
+            sp_keyring::AccountKeyring::Alice,
+            0,
+            &|t: ContractMessageTranscoder<'_>| t.encode::<_, String>("get", []).unwrap(),
+        )
+        .await
+        .and_then(|v| <bool>::decode(&mut &v[..]).map_err(Into::into))
+}
+
+#[tokio::test]
+async fn deploy_at_destined_address() -> anyhow::Result<()> {
+    let api = API::from_url(
+        std::env::var("ENDPOINT").unwrap_or_else(|_| "ws://127.0.0.1:9944".to_string()),
+    )
+    .await?;
+
+    // a fresh address every run, the chain keeps system contracts from previous ones
+    let destined: [u8; 32] = rand::random();
+
+    let mut contract = Contract::new("../contracts/flipper.contract")?;
+    contract
+        .deploy_as_system_contract(&api, Some(destined), 0, flipper_new("true"))
+        .await?;
+
+    let address = AccountId32::from(destined);
+    assert_eq!(contract.address(), Some(&address));
+    assert!(is_system_contract(&api, &address).await?);
+    assert!(system_contracts(&api).await?.contains(&address));
+    assert!(get(&api, &contract).await?);
+
+    // the same address can't be claimed twice
+    let mut again = Contract::new("../contracts/flipper.contract")?;
+    assert!(again
+        .deploy_as_system_contract(&api, Some(destined), 0, flipper_new("true"))
+        .await
+        .is_err());
+
+    // ordinary contracts are not registered
+    let mut ordinary = Contract::new("../contracts/flipper.contract")?;
+    ordinary
+        .deploy(
+            &api,
+            sp_keyring::AccountKeyring::Alice,
+            0,
+            flipper_new("true"),
+        )
+        .await?;
+
+    assert!(!is_system_contract(&api, ordinary.address().unwrap()).await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn upgrade_keeps_storage() -> anyhow::Result<()> {
+    let api = API::from_url(
+        std::env::var("ENDPOINT").unwrap_or_else(|_| "ws://127.0.0.1:9944".to_string()),
+    )
+    .await?;
+
+    let destined: [u8; 32] = rand::random();
+
+    let mut contract = Contract::new("../contracts/flipper.contract")?;
+    contract
+        .deploy_as_system_contract(&api, Some(destined), 0, flipper_new("true"))
+        .await?;
+
+    contract
+        .call(
+            &api,
+            sp_keyring::AccountKeyring::Alice,
+            0,
+            &|t: ContractMessageTranscoder<'_>| t.encode::<_, String>("flip", []).unwrap(),
+        )
+        .await?;
+    assert!(!get(&api, &contract).await?);
+
+    let upgraded = contract
+        .set_code(&api, "../contracts/flipper_v2.contract")
+        .await?;
+
+    assert_eq!(upgraded.address(), contract.address());
+    assert!(is_system_contract(&api, upgraded.address().unwrap()).await?);
+
+    // storage written by the old code is still there
+    assert!(!get(&api, &upgraded).await?);
+
+    let version = upgraded
+        .try_call(
+            &api,
+            sp_keyring::AccountKeyring::Alice,
+            0,
+            &|t: ContractMessageTranscoder<'_>| t.encode::<_, String>("version", []).unwrap(),
+        )
+        .await
+        .and_then(|v| <u32>::decode(&mut &v[..]).map_err(Into::into))?;
+    assert_eq!(version, 2);
+
+    upgraded
+        .call(
+            &api,
+            sp_keyring::AccountKeyring::Alice,
+            0,
+            &|t: ContractMessageTranscoder<'_>| t.encode("set", ["true"]).unwrap(),
+        )
+        .await?;
+    assert!(get(&api, &upgraded).await?);
+
+    // redeploying the old bundle at the same address downgrades it in place
+    let mut downgraded = Contract::new("../contracts/flipper.contract")?;
+    downgraded
+        .ensure_system_contract(&api, destined, 0, flipper_new("false"))
+        .await?;
+
+    assert_eq!(downgraded.address(), contract.address());
+    assert!(get(&api, &downgraded).await?);
+    assert!(upgraded
+        .try_call(
+            &api,
+            sp_keyring::AccountKeyring::Alice,
+            0,
+            &|t: ContractMessageTranscoder<'_>| t.encode::<_, String>("version", []).unwrap(),
+        )
+        .await
+        .is_err());
+
+    Ok(())
+}
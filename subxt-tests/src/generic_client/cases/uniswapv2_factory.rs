@@ -1,6 +1,8 @@
+use async_trait::async_trait;
 use contract_transcode::ContractMessageTranscoder;
 use hex::FromHex;
 use ink_metadata::InkProject;
+use once_cell::sync::Lazy;
 use parity_scale_codec::{Decode, DecodeAll, Encode, Input};
 use rand::Rng;
 use sp_core::{
@@ -10,6 +12,7 @@ use sp_core::{
 };
 
 use crate::generic_client::{
+    fixture::{Fixture, Setup},
     load_project, Contract, DeployContract, Execution, ReadContract, WriteContract, API,
 };
 
@@ -247,6 +250,22 @@ async fn set_fee_to_setter() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// The pair code `createPair` instantiates, uploaded once per test process.
+struct PairCode;
+
+#[async_trait]
+impl Setup for PairCode {
+    async fn deploy(api: &API) -> anyhow::Result<Self> {
+        Contract::new("../contracts/UniswapV2Pair.contract")?
+            .upload_code(api, sp_keyring::AccountKeyring::Alice)
+            .await?;
+
+        Ok(Self)
+    }
+}
+
+static PAIR_CODE: Lazy<Fixture<PairCode>> = Lazy::new(Fixture::new);
+
 struct MockWorld {
     factory: Contract,
 }
@@ -255,9 +274,7 @@ impl MockWorld {
     async fn init(api: &API) -> anyhow::Result<Self> {
         let mut contract = Contract::new("../contracts/UniswapV2Factory.contract")?;
 
-        Contract::new("../contracts/UniswapV2Pair.contract")?
-            .upload_code(api, sp_keyring::AccountKeyring::Alice)
-            .await?;
+        PAIR_CODE.get(api).await?;
 
         contract
             .deploy(
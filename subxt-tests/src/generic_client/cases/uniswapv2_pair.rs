@@ -1,18 +1,23 @@
 use std::ops::Mul;
 
+use async_trait::async_trait;
 use contract_transcode::ContractMessageTranscoder;
 use hex::FromHex;
 use ink_metadata::InkProject;
+use once_cell::sync::Lazy;
 use parity_scale_codec::{Decode, DecodeAll, Encode, Input};
 use rand::Rng;
 use sp_core::{
     crypto::{AccountId32, Ss58Codec},
     hexdisplay::AsBytesRef,
-    keccak_256, U256,
+    keccak_256, sr25519, Pair as _, U256,
 };
 
 use crate::generic_client::{
-    load_project, Contract, DeployContract, Execution, ReadContract, WriteContract, API,
+    fixture::{fresh_account, Fixture, Setup},
+    load_project,
+    manifest::{AddressBook, Manifest},
+    Contract, DeployContract, Execution, ReadContract, WriteContract, API,
 };
 
 #[tokio::test]
@@ -23,86 +28,78 @@ async fn mint() -> anyhow::Result<()> {
     .await?;
 
     let w = MockWorld::init(&api).await?;
+    let user = &w.user;
 
     let min_liquidity: U256 = U256::from(1000_u32);
 
     w.token_0
-        .call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| {
-                let mut s = t
-                    .encode::<_, String>(
-                        "transfer",
-                        [format!(
-                            "0x{}",
-                            hex::encode(w.pair.address.as_ref().unwrap())
-                        )],
-                    )
-                    .unwrap();
+        .call(&api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+            let mut s = t
+                .encode::<_, String>(
+                    "transfer",
+                    [format!(
+                        "0x{}",
+                        hex::encode(w.pair.address.as_ref().unwrap())
+                    )],
+                )
+                .unwrap();
 
-                U256::from(10_u8).pow(18_u8.into()).encode_to(&mut s);
+            U256::from(10_u8).pow(18_u8.into()).encode_to(&mut s);
 
-                s
-            },
-        )
+            s
+        })
         .await?;
 
     w.token_1
-        .call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| {
-                let mut s = t
-                    .encode::<_, String>(
-                        "transfer",
-                        [format!(
-                            "0x{}",
-                            hex::encode(w.pair.address.as_ref().unwrap())
-                        )],
-                    )
-                    .unwrap();
+        .call(&api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+            let mut s = t
+                .encode::<_, String>(
+                    "transfer",
+                    [format!(
+                        "0x{}",
+                        hex::encode(w.pair.address.as_ref().unwrap())
+                    )],
+                )
+                .unwrap();
 
-                U256::from(10_u8)
-                    .pow(18_u8.into())
-                    .mul(U256::from(4_u8))
-                    .encode_to(&mut s);
+            U256::from(10_u8)
+                .pow(18_u8.into())
+                .mul(U256::from(4_u8))
+                .encode_to(&mut s);
 
-                s
-            },
-        )
+            s
+        })
         .await?;
 
     let expected_liquidity = U256::from(10_u8).pow(18_u8.into()).mul(U256::from(2_u8));
 
+    let before = w.pair.snapshot(&api).await?;
+
     w.pair
-        .call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| {
-                t.encode::<_, String>(
-                    "mint",
-                    [format!(
-                        "0x{}",
-                        hex::encode(sp_keyring::AccountKeyring::Alice.to_account_id())
-                    )],
-                )
+        .call(&api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>("mint", [format!("0x{}", hex::encode(user.public()))])
                 .unwrap()
-            },
-        )
+        })
         .await?;
 
+    // mint only touches balances, reserves and the accumulators, never the pair's identity
+    let diff = before.diff(&w.pair.snapshot(&api).await?);
+    let layout = w.pair.storage_layout();
+
+    for field in ["factory", "token0", "token1"] {
+        assert!(
+            diff.get(&layout.field(field)?.key).is_none(),
+            "unexpected write to {field}:\n{}",
+            diff.render(&w.pair.storage_labels())
+        );
+    }
+    assert!(diff.inserted().count() > 0);
+
     let total_supply = w
         .pair
-        .try_call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| t.encode::<_, String>("totalSupply", []).unwrap(),
-        )
+        .try_call(&api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>("totalSupply", []).unwrap()
+        })
         .await
         .and_then(|v| <U256>::decode(&mut &v[..]).map_err(Into::into))?;
 
@@ -110,21 +107,10 @@ async fn mint() -> anyhow::Result<()> {
 
     let balance = w
         .pair
-        .try_call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| {
-                t.encode::<_, String>(
-                    "balanceOf",
-                    [format!(
-                        "0x{}",
-                        hex::encode(sp_keyring::AccountKeyring::Alice.to_account_id())
-                    )],
-                )
+        .try_call(&api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>("balanceOf", [format!("0x{}", hex::encode(user.public()))])
                 .unwrap()
-            },
-        )
+        })
         .await
         .and_then(|v| <U256>::decode(&mut &v[..]).map_err(Into::into))?;
 
@@ -132,21 +118,16 @@ async fn mint() -> anyhow::Result<()> {
 
     let balance_0 = w
         .token_0
-        .try_call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| {
-                t.encode::<_, String>(
-                    "balanceOf",
-                    [format!(
-                        "0x{}",
-                        hex::encode(w.pair.address.as_ref().unwrap())
-                    )],
-                )
-                .unwrap()
-            },
-        )
+        .try_call(&api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>(
+                "balanceOf",
+                [format!(
+                    "0x{}",
+                    hex::encode(w.pair.address.as_ref().unwrap())
+                )],
+            )
+            .unwrap()
+        })
         .await
         .and_then(|v| <U256>::decode(&mut &v[..]).map_err(Into::into))?;
 
@@ -154,21 +135,16 @@ async fn mint() -> anyhow::Result<()> {
 
     let balance_1 = w
         .token_1
-        .try_call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| {
-                t.encode::<_, String>(
-                    "balanceOf",
-                    [format!(
-                        "0x{}",
-                        hex::encode(w.pair.address.as_ref().unwrap())
-                    )],
-                )
-                .unwrap()
-            },
-        )
+        .try_call(&api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>(
+                "balanceOf",
+                [format!(
+                    "0x{}",
+                    hex::encode(w.pair.address.as_ref().unwrap())
+                )],
+            )
+            .unwrap()
+        })
         .await
         .and_then(|v| <U256>::decode(&mut &v[..]).map_err(Into::into))?;
 
@@ -179,12 +155,9 @@ async fn mint() -> anyhow::Result<()> {
 
     let reserves = w
         .pair
-        .try_call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| t.encode::<_, String>("getReserves", []).unwrap(),
-        )
+        .try_call(&api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>("getReserves", []).unwrap()
+        })
         .await?;
 
     let t = ContractMessageTranscoder::new(&w.pair.project);
@@ -234,6 +207,7 @@ async fn swap_token0() -> anyhow::Result<()> {
     let token1_amount = U256::from(10_u8).pow(U256::from(19_u8));
 
     let w = MockWorld::init(&api).await?;
+    let user = &w.user;
 
     let min_liquidity: U256 = U256::from(1000_u32);
 
@@ -244,56 +218,43 @@ async fn swap_token0() -> anyhow::Result<()> {
     let expected_output = U256::from_dec_str("1662497915624478906")?;
 
     w.token_0
-        .call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| {
-                let mut s = t
-                    .encode::<_, String>(
-                        "transfer",
-                        [format!(
-                            "0x{}",
-                            hex::encode(w.pair.address.as_ref().unwrap())
-                        )],
-                    )
-                    .unwrap();
+        .call(&api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+            let mut s = t
+                .encode::<_, String>(
+                    "transfer",
+                    [format!(
+                        "0x{}",
+                        hex::encode(w.pair.address.as_ref().unwrap())
+                    )],
+                )
+                .unwrap();
 
-                swap_amount.encode_to(&mut s);
-                s
-            },
-        )
+            swap_amount.encode_to(&mut s);
+            s
+        })
         .await?;
 
     w.pair
-        .call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| {
-                let mut s = t.encode::<_, String>("swap", []).unwrap();
-
-                (
-                    U256::zero(),
-                    expected_output,
-                    sp_keyring::AccountKeyring::Alice.to_account_id(),
-                    "",
-                )
-                    .encode_to(&mut s);
+        .call(&api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+            let mut s = t.encode::<_, String>("swap", []).unwrap();
+
+            (
+                U256::zero(),
+                expected_output,
+                AccountId32::from(user.public()),
+                "",
+            )
+                .encode_to(&mut s);
 
-                s
-            },
-        )
+            s
+        })
         .await?;
 
     let out = w
         .pair
-        .try_call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| t.encode::<_, String>("getReserves", []).unwrap(),
-        )
+        .try_call(&api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>("getReserves", []).unwrap()
+        })
         .await
         .and_then(|v| {
             let t = ContractMessageTranscoder::new(&w.pair.project);
@@ -329,21 +290,16 @@ async fn swap_token0() -> anyhow::Result<()> {
 
     let bal_0 = w
         .token_0
-        .try_call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| {
-                t.encode::<_, String>(
-                    "balanceOf",
-                    [format!(
-                        "0x{}",
-                        hex::encode(w.pair.address.as_ref().unwrap())
-                    )],
-                )
-                .unwrap()
-            },
-        )
+        .try_call(&api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>(
+                "balanceOf",
+                [format!(
+                    "0x{}",
+                    hex::encode(w.pair.address.as_ref().unwrap())
+                )],
+            )
+            .unwrap()
+        })
         .await
         .and_then(|v| <U256>::decode(&mut &v[..]).map_err(Into::into))?;
 
@@ -351,89 +307,46 @@ async fn swap_token0() -> anyhow::Result<()> {
 
     let bal_1 = w
         .token_1
-        .try_call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| {
-                t.encode::<_, String>(
-                    "balanceOf",
-                    [format!(
-                        "0x{}",
-                        hex::encode(w.pair.address.as_ref().unwrap())
-                    )],
-                )
-                .unwrap()
-            },
-        )
+        .try_call(&api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>(
+                "balanceOf",
+                [format!(
+                    "0x{}",
+                    hex::encode(w.pair.address.as_ref().unwrap())
+                )],
+            )
+            .unwrap()
+        })
         .await
         .and_then(|v| <U256>::decode(&mut &v[..]).map_err(Into::into))?;
     assert_eq!(bal_1, token1_amount - expected_output);
 
-    let supply_0 = w
-        .token_0
-        .try_call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| t.encode::<_, String>("totalSupply", []).unwrap(),
-        )
-        .await
-        .and_then(|v| <U256>::decode(&mut &v[..]).map_err(Into::into))?;
-
-    let supply_1 = w
-        .token_1
-        .try_call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| t.encode::<_, String>("totalSupply", []).unwrap(),
-        )
-        .await
-        .and_then(|v| <U256>::decode(&mut &v[..]).map_err(Into::into))?;
-
     let wallet_balance_0 = w
         .token_0
-        .try_call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| {
-                t.encode::<_, String>(
-                    "balanceOf",
-                    [format!(
-                        "0x{}",
-                        hex::encode(sp_keyring::AccountKeyring::Alice.to_account_id())
-                    )],
-                )
+        .try_call(&api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>("balanceOf", [format!("0x{}", hex::encode(user.public()))])
                 .unwrap()
-            },
-        )
+        })
         .await
         .and_then(|v| <U256>::decode(&mut &v[..]).map_err(Into::into))?;
 
     let wallet_balance_1 = w
         .token_1
-        .try_call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| {
-                t.encode::<_, String>(
-                    "balanceOf",
-                    [format!(
-                        "0x{}",
-                        hex::encode(sp_keyring::AccountKeyring::Alice.to_account_id())
-                    )],
-                )
+        .try_call(&api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>("balanceOf", [format!("0x{}", hex::encode(user.public()))])
                 .unwrap()
-            },
-        )
+        })
         .await
         .and_then(|v| <U256>::decode(&mut &v[..]).map_err(Into::into))?;
 
-    assert_eq!(wallet_balance_0, supply_0 - token0_amount - swap_amount,);
-    assert_eq!(wallet_balance_1, supply_1 - token1_amount + expected_output);
+    assert_eq!(
+        wallet_balance_0,
+        token_endowment() - token0_amount - swap_amount,
+    );
+    assert_eq!(
+        wallet_balance_1,
+        token_endowment() - token1_amount + expected_output
+    );
 
     Ok(())
 }
@@ -452,6 +365,7 @@ async fn swap_token1() -> anyhow::Result<()> {
     let token1_amount = U256::from(10_u8).pow(U256::from(19_u8));
 
     let w = MockWorld::init(&api).await?;
+    let user = &w.user;
 
     let min_liquidity: U256 = U256::from(1000_u32);
 
@@ -462,56 +376,43 @@ async fn swap_token1() -> anyhow::Result<()> {
     let expected_output = U256::from_dec_str("453305446940074565")?;
 
     w.token_1
-        .call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| {
-                let mut s = t
-                    .encode::<_, String>(
-                        "transfer",
-                        [format!(
-                            "0x{}",
-                            hex::encode(w.pair.address.as_ref().unwrap())
-                        )],
-                    )
-                    .unwrap();
+        .call(&api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+            let mut s = t
+                .encode::<_, String>(
+                    "transfer",
+                    [format!(
+                        "0x{}",
+                        hex::encode(w.pair.address.as_ref().unwrap())
+                    )],
+                )
+                .unwrap();
 
-                swap_amount.encode_to(&mut s);
-                s
-            },
-        )
+            swap_amount.encode_to(&mut s);
+            s
+        })
         .await?;
 
     w.pair
-        .call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| {
-                let mut s = t.encode::<_, String>("swap", []).unwrap();
-
-                (
-                    expected_output,
-                    U256::zero(),
-                    sp_keyring::AccountKeyring::Alice.to_account_id(),
-                    "",
-                )
-                    .encode_to(&mut s);
+        .call(&api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+            let mut s = t.encode::<_, String>("swap", []).unwrap();
+
+            (
+                expected_output,
+                U256::zero(),
+                AccountId32::from(user.public()),
+                "",
+            )
+                .encode_to(&mut s);
 
-                s
-            },
-        )
+            s
+        })
         .await?;
 
     let out = w
         .pair
-        .try_call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| t.encode::<_, String>("getReserves", []).unwrap(),
-        )
+        .try_call(&api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>("getReserves", []).unwrap()
+        })
         .await
         .and_then(|v| {
             let t = ContractMessageTranscoder::new(&w.pair.project);
@@ -547,21 +448,16 @@ async fn swap_token1() -> anyhow::Result<()> {
 
     let bal_0 = w
         .token_0
-        .try_call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| {
-                t.encode::<_, String>(
-                    "balanceOf",
-                    [format!(
-                        "0x{}",
-                        hex::encode(w.pair.address.as_ref().unwrap())
-                    )],
-                )
-                .unwrap()
-            },
-        )
+        .try_call(&api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>(
+                "balanceOf",
+                [format!(
+                    "0x{}",
+                    hex::encode(w.pair.address.as_ref().unwrap())
+                )],
+            )
+            .unwrap()
+        })
         .await
         .and_then(|v| <U256>::decode(&mut &v[..]).map_err(Into::into))?;
 
@@ -569,89 +465,46 @@ async fn swap_token1() -> anyhow::Result<()> {
 
     let bal_1 = w
         .token_1
-        .try_call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| {
-                t.encode::<_, String>(
-                    "balanceOf",
-                    [format!(
-                        "0x{}",
-                        hex::encode(w.pair.address.as_ref().unwrap())
-                    )],
-                )
-                .unwrap()
-            },
-        )
+        .try_call(&api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>(
+                "balanceOf",
+                [format!(
+                    "0x{}",
+                    hex::encode(w.pair.address.as_ref().unwrap())
+                )],
+            )
+            .unwrap()
+        })
         .await
         .and_then(|v| <U256>::decode(&mut &v[..]).map_err(Into::into))?;
     assert_eq!(bal_1, token1_amount + swap_amount);
 
-    let supply_0 = w
-        .token_0
-        .try_call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| t.encode::<_, String>("totalSupply", []).unwrap(),
-        )
-        .await
-        .and_then(|v| <U256>::decode(&mut &v[..]).map_err(Into::into))?;
-
-    let supply_1 = w
-        .token_1
-        .try_call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| t.encode::<_, String>("totalSupply", []).unwrap(),
-        )
-        .await
-        .and_then(|v| <U256>::decode(&mut &v[..]).map_err(Into::into))?;
-
     let wallet_balance_0 = w
         .token_0
-        .try_call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| {
-                t.encode::<_, String>(
-                    "balanceOf",
-                    [format!(
-                        "0x{}",
-                        hex::encode(sp_keyring::AccountKeyring::Alice.to_account_id())
-                    )],
-                )
+        .try_call(&api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>("balanceOf", [format!("0x{}", hex::encode(user.public()))])
                 .unwrap()
-            },
-        )
+        })
         .await
         .and_then(|v| <U256>::decode(&mut &v[..]).map_err(Into::into))?;
 
     let wallet_balance_1 = w
         .token_1
-        .try_call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| {
-                t.encode::<_, String>(
-                    "balanceOf",
-                    [format!(
-                        "0x{}",
-                        hex::encode(sp_keyring::AccountKeyring::Alice.to_account_id())
-                    )],
-                )
+        .try_call(&api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>("balanceOf", [format!("0x{}", hex::encode(user.public()))])
                 .unwrap()
-            },
-        )
+        })
         .await
         .and_then(|v| <U256>::decode(&mut &v[..]).map_err(Into::into))?;
 
-    assert_eq!(wallet_balance_0, supply_0 - token0_amount + expected_output);
-    assert_eq!(wallet_balance_1, supply_1 - token1_amount - swap_amount);
+    assert_eq!(
+        wallet_balance_0,
+        token_endowment() - token0_amount + expected_output
+    );
+    assert_eq!(
+        wallet_balance_1,
+        token_endowment() - token1_amount - swap_amount
+    );
 
     Ok(())
 }
@@ -664,68 +517,42 @@ async fn burn() -> anyhow::Result<()> {
     .await?;
 
     let w = MockWorld::init(&api).await?;
+    let user = &w.user;
 
     let token_amount = U256::from(10_u8).pow(18_u8.into()).mul(U256::from(3_u8));
 
     w.add_liquitity(&api, &token_amount, &token_amount).await?;
 
     w.pair
-        .call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| {
-                let mut s = t
-                    .encode::<_, String>(
-                        "transfer",
-                        [format!(
-                            "0x{}",
-                            hex::encode(w.pair.address.as_ref().unwrap())
-                        )],
-                    )
-                    .unwrap();
-
-                (token_amount - U256::from(1000_u32)).encode_to(&mut s);
-                s
-            },
-        )
-        .await?;
-
-    w.pair
-        .call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| {
-                t.encode::<_, String>(
-                    "burn",
+        .call(&api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+            let mut s = t
+                .encode::<_, String>(
+                    "transfer",
                     [format!(
                         "0x{}",
-                        hex::encode(sp_keyring::AccountKeyring::Alice.to_account_id())
+                        hex::encode(w.pair.address.as_ref().unwrap())
                     )],
                 )
+                .unwrap();
+
+            (token_amount - U256::from(1000_u32)).encode_to(&mut s);
+            s
+        })
+        .await?;
+
+    w.pair
+        .call(&api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>("burn", [format!("0x{}", hex::encode(user.public()))])
                 .unwrap()
-            },
-        )
+        })
         .await?;
 
     let wallet_balance_0 = w
         .pair
-        .try_call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| {
-                t.encode::<_, String>(
-                    "balanceOf",
-                    [format!(
-                        "0x{}",
-                        hex::encode(sp_keyring::AccountKeyring::Alice.to_account_id())
-                    )],
-                )
+        .try_call(&api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>("balanceOf", [format!("0x{}", hex::encode(user.public()))])
                 .unwrap()
-            },
-        )
+        })
         .await
         .and_then(|v| <U256>::decode(&mut &v[..]).map_err(Into::into))?;
 
@@ -733,12 +560,9 @@ async fn burn() -> anyhow::Result<()> {
 
     let pair_supply = w
         .pair
-        .try_call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| t.encode::<_, String>("totalSupply", []).unwrap(),
-        )
+        .try_call(&api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>("totalSupply", []).unwrap()
+        })
         .await
         .and_then(|v| <U256>::decode(&mut &v[..]).map_err(Into::into))?;
 
@@ -746,21 +570,16 @@ async fn burn() -> anyhow::Result<()> {
 
     let pair_balance_0 = w
         .token_0
-        .try_call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| {
-                t.encode::<_, String>(
-                    "balanceOf",
-                    [format!(
-                        "0x{}",
-                        hex::encode(w.pair.address.as_ref().unwrap())
-                    )],
-                )
-                .unwrap()
-            },
-        )
+        .try_call(&api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>(
+                "balanceOf",
+                [format!(
+                    "0x{}",
+                    hex::encode(w.pair.address.as_ref().unwrap())
+                )],
+            )
+            .unwrap()
+        })
         .await
         .and_then(|v| <U256>::decode(&mut &v[..]).map_err(Into::into))?;
 
@@ -768,221 +587,125 @@ async fn burn() -> anyhow::Result<()> {
 
     let pair_balance_1 = w
         .token_1
-        .try_call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| {
-                t.encode::<_, String>(
-                    "balanceOf",
-                    [format!(
-                        "0x{}",
-                        hex::encode(w.pair.address.as_ref().unwrap())
-                    )],
-                )
-                .unwrap()
-            },
-        )
+        .try_call(&api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>(
+                "balanceOf",
+                [format!(
+                    "0x{}",
+                    hex::encode(w.pair.address.as_ref().unwrap())
+                )],
+            )
+            .unwrap()
+        })
         .await
         .and_then(|v| <U256>::decode(&mut &v[..]).map_err(Into::into))?;
 
     assert_eq!(pair_balance_1, U256::from(1000_u32));
 
-    let supply_0 = w
-        .token_0
-        .try_call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| t.encode::<_, String>("totalSupply", []).unwrap(),
-        )
-        .await
-        .and_then(|v| <U256>::decode(&mut &v[..]).map_err(Into::into))?;
-
-    let supply_1 = w
-        .token_1
-        .try_call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| t.encode::<_, String>("totalSupply", []).unwrap(),
-        )
-        .await
-        .and_then(|v| <U256>::decode(&mut &v[..]).map_err(Into::into))?;
-
     let wallet_balance_0 = w
         .token_0
-        .try_call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| {
-                t.encode::<_, String>(
-                    "balanceOf",
-                    [format!(
-                        "0x{}",
-                        hex::encode(sp_keyring::AccountKeyring::Alice.to_account_id())
-                    )],
-                )
+        .try_call(&api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>("balanceOf", [format!("0x{}", hex::encode(user.public()))])
                 .unwrap()
-            },
-        )
+        })
         .await
         .and_then(|v| <U256>::decode(&mut &v[..]).map_err(Into::into))?;
 
     let wallet_balance_1 = w
         .token_1
-        .try_call(
-            &api,
-            sp_keyring::AccountKeyring::Alice,
-            0,
-            &|t: ContractMessageTranscoder<'_>| {
-                t.encode::<_, String>(
-                    "balanceOf",
-                    [format!(
-                        "0x{}",
-                        hex::encode(sp_keyring::AccountKeyring::Alice.to_account_id())
-                    )],
-                )
+        .try_call(&api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+            t.encode::<_, String>("balanceOf", [format!("0x{}", hex::encode(user.public()))])
                 .unwrap()
-            },
-        )
+        })
         .await
         .and_then(|v| <U256>::decode(&mut &v[..]).map_err(Into::into))?;
 
-    assert_eq!(wallet_balance_0, supply_0 - U256::from(1000_u32));
-    assert_eq!(wallet_balance_1, supply_1 - U256::from(1000_u32));
+    assert_eq!(wallet_balance_0, token_endowment() - U256::from(1000_u32));
+    assert_eq!(wallet_balance_1, token_endowment() - U256::from(1000_u32));
 
     Ok(())
 }
-struct MockWorld {
-    factory: Contract,
-    pair: Contract,
-    token_0: Contract,
-    token_1: Contract,
-}
-
-impl MockWorld {
-    async fn init(api: &API) -> anyhow::Result<Self> {
-        let mut factory = Contract::new("../contracts/UniswapV2Factory.contract")?;
-
-        factory
-            .deploy(
-                api,
-                sp_keyring::AccountKeyring::Alice,
-                10_u128.pow(16),
-                &|t: ContractMessageTranscoder<'_>| {
-                    t.encode::<_, String>(
-                        "new",
-                        [format!(
-                            "0x{}",
-                            hex::encode(&sp_keyring::AccountKeyring::Alice.to_account_id())
-                        )],
-                    )
-                    .unwrap()
-                },
-            )
-            .await?;
-
-        let mut pair = Contract::new("../contracts/UniswapV2Pair.contract")?;
-
-        factory
-            .upload_code(api, sp_keyring::AccountKeyring::Alice)
+/// ERC20 tokens and the pair code, deployed once and shared by every Uniswap V2 test.
+pub(super) struct Tokens(AddressBook);
+
+#[async_trait]
+impl Setup for Tokens {
+    async fn deploy(api: &API) -> anyhow::Result<Self> {
+        let world = Manifest::load("manifests/uniswapv2_tokens.toml")?
+            .deploy(api, &sp_keyring::AccountKeyring::Alice.pair())
             .await?;
 
-        let mut token_a = Contract::new("../contracts/ERC20.contract")?;
-        token_a
-            .deploy(
-                api,
-                sp_keyring::AccountKeyring::Alice,
-                0,
-                &|t: ContractMessageTranscoder<'_>| {
-                    let mut selector = t.encode::<_, String>("new", []).unwrap();
-
-                    U256::from(10_u8).pow(22_u8.into()).encode_to(&mut selector);
+        Ok(Self(world.addresses().clone()))
+    }
+}
 
-                    selector
-                },
-            )
-            .await?;
+pub(super) static TOKENS: Lazy<Fixture<Tokens>> = Lazy::new(Fixture::new);
 
-        let mut token_b = Contract::new("../contracts/ERC20.contract")?;
-        token_b
-            .deploy(
-                api,
-                sp_keyring::AccountKeyring::Alice,
-                0,
-                &|t: ContractMessageTranscoder<'_>| {
-                    let mut selector = t.encode::<_, String>("new", []).unwrap();
+/// Tokens of each kind `MockWorld::user` starts out with.
+pub(super) fn token_endowment() -> U256 {
+    U256::from(10_u8).pow(20_u8.into())
+}
 
-                    U256::from(10_u8).pow(22_u8.into()).encode_to(&mut selector);
+/// A fresh factory and pair on the shared tokens, plus a fresh account funded with native tokens
+/// and `token_endowment()` of both tokens. Tests sign as `user`, so balances left behind in the
+/// shared tokens by other tests don't show up in theirs.
+pub(super) struct MockWorld {
+    pub(super) factory: Contract,
+    pub(super) pair: Contract,
+    pub(super) token_0: Contract,
+    pub(super) token_1: Contract,
+    pub(super) user: sr25519::Pair,
+}
 
-                    selector
-                },
-            )
-            .await?;
+impl MockWorld {
+    pub(super) async fn init(api: &API) -> anyhow::Result<Self> {
+        let tokens = TOKENS.get(api).await?;
 
-        factory
-            .call(
+        let mut world = Manifest::load("manifests/uniswapv2_pair.toml")?
+            .deploy_with(
                 api,
-                sp_keyring::AccountKeyring::Alice,
-                0,
-                &|t: ContractMessageTranscoder<'_>| {
-                    t.encode::<_, String>(
-                        "createPair",
-                        [
-                            format!("0x{}", hex::encode(token_a.address.as_ref().unwrap())),
-                            format!("0x{}", hex::encode(token_b.address.as_ref().unwrap())),
-                        ],
-                    )
-                    .unwrap()
-                },
+                &sp_keyring::AccountKeyring::Alice.pair(),
+                tokens.0.clone(),
             )
             .await?;
 
-        let pair_addr = factory
-            .try_call(
-                api,
-                sp_keyring::AccountKeyring::Alice,
-                0,
-                &|t: ContractMessageTranscoder<'_>| {
-                    t.encode::<_, String>(
-                        "getPair",
-                        [
-                            format!("0x{}", hex::encode(token_a.address.as_ref().unwrap())),
-                            format!("0x{}", hex::encode(token_b.address.as_ref().unwrap())),
-                        ],
-                    )
-                    .unwrap()
-                },
-            )
-            .await
-            .and_then(|v| <AccountId32>::decode(&mut &v[..]).map_err(Into::into))?;
-
-        pair = pair.from_addr(pair_addr)?;
-
-        let token_0_addr = pair
-            .try_call(
-                api,
-                sp_keyring::AccountKeyring::Alice,
-                0,
-                &|t: ContractMessageTranscoder<'_>| t.encode::<_, String>("token0", []).unwrap(),
-            )
-            .await
-            .and_then(|v| <AccountId32>::decode(&mut &v[..]).map_err(Into::into))?;
-
-        let (token_0, token_1) = if *token_a.address.as_ref().unwrap() == token_0_addr {
+        let token_a = tokens.0.get("token_a")?;
+        let token_b = tokens.0.get("token_b")?;
+        let (token_0, token_1) = if world.addresses().get("token0")? == token_a {
             (token_a, token_b)
         } else {
             (token_b, token_a)
         };
 
-        Ok(Self {
-            factory,
-            pair,
-            token_0,
-            token_1,
-        })
+        let erc20 = Contract::new("../contracts/ERC20.contract")?;
+
+        let w = Self {
+            factory: world.take("factory")?,
+            pair: world.take("pair")?,
+            token_0: erc20.from_addr(token_0.clone())?,
+            token_1: erc20.from_addr(token_1.clone())?,
+            user: fresh_account(api, 10_u128.pow(18)).await?,
+        };
+
+        for token in [&w.token_0, &w.token_1] {
+            token
+                .call(
+                    api,
+                    sp_keyring::AccountKeyring::Alice,
+                    0,
+                    &|t: ContractMessageTranscoder<'_>| {
+                        let mut s = t
+                            .encode("transfer", [format!("0x{}", hex::encode(w.user.public()))])
+                            .unwrap();
+
+                        token_endowment().encode_to(&mut s);
+                        s
+                    },
+                )
+                .await?;
+        }
+
+        Ok(w)
     }
 
     async fn add_liquitity(
@@ -991,66 +714,47 @@ impl MockWorld {
         amount_a: &U256,
         amount_b: &U256,
     ) -> anyhow::Result<()> {
+        let user = &self.user;
+
         self.token_0
-            .call(
-                api,
-                sp_keyring::AccountKeyring::Alice,
-                0,
-                &|t: ContractMessageTranscoder<'_>| {
-                    let mut s = t
-                        .encode(
-                            "transfer",
-                            [format!(
-                                "0x{}",
-                                hex::encode(self.pair.address.as_ref().unwrap())
-                            )],
-                        )
-                        .unwrap();
-
-                    amount_a.encode_to(&mut s);
-                    s
-                },
-            )
-            .await?;
+            .call(api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+                let mut s = t
+                    .encode(
+                        "transfer",
+                        [format!(
+                            "0x{}",
+                            hex::encode(self.pair.address.as_ref().unwrap())
+                        )],
+                    )
+                    .unwrap();
 
-        self.token_1
-            .call(
-                api,
-                sp_keyring::AccountKeyring::Alice,
-                0,
-                &|t: ContractMessageTranscoder<'_>| {
-                    let mut s = t
-                        .encode(
-                            "transfer",
-                            [format!(
-                                "0x{}",
-                                hex::encode(self.pair.address.as_ref().unwrap())
-                            )],
-                        )
-                        .unwrap();
-
-                    amount_b.encode_to(&mut s);
-                    s
-                },
-            )
+                amount_a.encode_to(&mut s);
+                s
+            })
             .await?;
 
-        self.pair
-            .call(
-                api,
-                sp_keyring::AccountKeyring::Alice,
-                0,
-                &|t: ContractMessageTranscoder<'_>| {
-                    t.encode(
-                        "mint",
+        self.token_1
+            .call(api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+                let mut s = t
+                    .encode(
+                        "transfer",
                         [format!(
                             "0x{}",
-                            hex::encode(sp_keyring::AccountKeyring::Alice.to_account_id())
+                            hex::encode(self.pair.address.as_ref().unwrap())
                         )],
                     )
+                    .unwrap();
+
+                amount_b.encode_to(&mut s);
+                s
+            })
+            .await?;
+
+        self.pair
+            .call(api, user, 0, &|t: ContractMessageTranscoder<'_>| {
+                t.encode("mint", [format!("0x{}", hex::encode(user.public()))])
                     .unwrap()
-                },
-            )
+            })
             .await?;
 
         Ok(())
@@ -0,0 +1,102 @@
+//! Worlds deployed once per test process and shared between tests.
+//!
+//! Every `#[tokio::test]` runs on its own runtime, so a fixture only caches plain data
+//! (`Contract`s, addresses) and never the client it was deployed with. Sharing is opt-in: a test
+//! that needs pristine contracts calls `Setup::deploy` itself, one that can live with state left
+//! behind by other tests goes through a `Fixture`. Tests then build an isolated view on top, e.g.
+//! a fresh pair on shared tokens, signed by a fresh account.
+//!
+//! ```ignore
+//! static TOKENS: Lazy<Fixture<Tokens>> = Lazy::new(Fixture::new);
+//!
+//! let tokens = TOKENS.get(&api).await?;
+//! ```
+//!
+//! Set `FRESH_FIXTURES=1` to redeploy on every `get`, e.g. when a test is suspected of depending
+//! on state leaked by another one.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use sp_core::{crypto::AccountId32, sr25519, Pair as _};
+use sp_keyring::AccountKeyring;
+use subxt::tx::PairSigner;
+use tokio::sync::Mutex;
+
+use crate::{node, utils::native_currency, API};
+
+#[async_trait]
+pub trait Setup: Sized + Send + Sync + 'static {
+    async fn deploy(api: &API) -> anyhow::Result<Self>;
+
+    /// Bring a cached world back to a known state before it's handed to the next test.
+    async fn reset(&self, _api: &API) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+pub struct Fixture<W> {
+    cached: Mutex<Option<Arc<W>>>,
+}
+
+impl<W: Setup> Default for Fixture<W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<W: Setup> Fixture<W> {
+    pub fn new() -> Self {
+        Self {
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// Deploy the world on first use, reset and reuse it afterwards.
+    pub async fn get(&self, api: &API) -> anyhow::Result<Arc<W>> {
+        let mut cached = self.cached.lock().await;
+
+        if let Some(w) = cached.as_ref().filter(|_| !fresh()) {
+            w.reset(api).await?;
+
+            return Ok(w.clone());
+        }
+
+        let w = Arc::new(W::deploy(api).await?);
+        cached.replace(w.clone());
+
+        Ok(w)
+    }
+
+    /// Forget the cached world, the next `get` deploys a new one.
+    pub async fn invalidate(&self) {
+        self.cached.lock().await.take();
+    }
+}
+
+fn fresh() -> bool {
+    std::env::var("FRESH_FIXTURES").map_or(false, |v| v == "1")
+}
+
+/// A random account endowed with `endowment` native tokens by Alice.
+pub async fn fresh_account(api: &API, endowment: u128) -> anyhow::Result<sr25519::Pair> {
+    let (pair, _) = sr25519::Pair::generate();
+    let account: AccountId32 = pair.public().into();
+
+    let payload = node::tx()
+        .currencies()
+        .transfer(account, native_currency(), endowment);
+
+    api.tx()
+        .sign_and_submit_then_watch_default(
+            &payload,
+            &PairSigner::new(AccountKeyring::Alice.pair()),
+        )
+        .await?
+        .wait_for_in_block()
+        .await?
+        .wait_for_success()
+        .await?;
+
+    Ok(pair)
+}
@@ -0,0 +1,557 @@
+//! metadata-driven property fuzzing of contract messages
+//!
+//! Argument types are read from the `InkProject` type registry, random but well-typed values are
+//! generated for them (integers favour their boundaries, account ids come from the test keyring)
+//! and the message is dry-run through `ContractsApi_call`. A run fails when the contract traps,
+//! or the call fails with any other dispatch error, and the caller doesn't expect it; the failing
+//! input is then shrunk before it is reported, so the error shows a minimal reproduction.
+//!
+//! Inputs are drawn from a seeded rng. The seed is random unless `FUZZ_SEED` is set, and it is
+//! reported with every failure so the run can be repeated.
+
+use std::fmt;
+
+use anyhow::anyhow;
+use ink_metadata::InkProject;
+use parity_scale_codec::{Compact, Encode};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use scale_info::{PortableRegistry, TypeDef, TypeDefPrimitive};
+use sp_core::{crypto::AccountId32, U256};
+use sp_keyring::AccountKeyring;
+use subxt::ext::sp_runtime::DispatchError;
+
+use super::{dry_run_call, API};
+
+const MAX_SEQ_LEN: usize = 4;
+const MAX_STR_LEN: usize = 12;
+const MAX_SHRINK_STEPS: usize = 256;
+
+/// The seed set in the environment variable `var`, or a random one
+pub fn seed_from_env(var: &str) -> u64 {
+    match std::env::var(var) {
+        Ok(seed) => seed
+            .parse()
+            .unwrap_or_else(|_| panic!("{var} must be a u64, found `{seed}`")),
+        Err(_) => rand::random(),
+    }
+}
+
+/// A generated argument, kept structured so it can be shrunk and inspected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Arg {
+    Bool(bool),
+    Char(char),
+    Str(String),
+    /// an integer of `width` bytes, stored as sign and magnitude
+    Int {
+        width: usize,
+        signed: bool,
+        neg: bool,
+        mag: U256,
+    },
+    AccountId([u8; 32]),
+    /// a sequence, encoded with its compact length
+    Seq(Vec<Arg>),
+    /// fixed arrays, tuples and structs
+    Fields(Vec<Arg>),
+    Variant(u8, Vec<Arg>),
+}
+
+impl Arg {
+    pub fn is_zero(&self) -> bool {
+        matches!(self, Arg::Int { mag, .. } if mag.is_zero())
+    }
+
+    /// whether this is the largest value of an unsigned integer
+    pub fn is_unsigned_max(&self) -> bool {
+        matches!(
+            self,
+            Arg::Int { width, signed: false, mag, .. } if *mag == U256::MAX >> (256 - width * 8)
+        )
+    }
+
+    /// variant index of an enum argument
+    pub fn variant(&self) -> Option<u8> {
+        match self {
+            Arg::Variant(i, _) => Some(*i),
+            _ => None,
+        }
+    }
+
+    pub fn encode_to(&self, out: &mut Vec<u8>) {
+        match self {
+            Arg::Bool(b) => b.encode_to(out),
+            Arg::Char(c) => (*c as u32).encode_to(out),
+            Arg::Str(s) => s.encode_to(out),
+            Arg::Int {
+                width, neg, mag, ..
+            } => {
+                let v = if *neg {
+                    (!*mag).overflowing_add(U256::one()).0
+                } else {
+                    *mag
+                };
+                let mut le = [0_u8; 32];
+                v.to_little_endian(&mut le);
+                out.extend_from_slice(&le[..*width]);
+            }
+            Arg::AccountId(a) => out.extend_from_slice(a),
+            Arg::Seq(items) => {
+                Compact(items.len() as u32).encode_to(out);
+                items.iter().for_each(|i| i.encode_to(out));
+            }
+            Arg::Fields(items) => items.iter().for_each(|i| i.encode_to(out)),
+            Arg::Variant(index, fields) => {
+                out.push(*index);
+                fields.iter().for_each(|i| i.encode_to(out));
+            }
+        }
+    }
+
+    /// Strictly simpler candidates, simplest first.
+    fn shrink(&self) -> Vec<Arg> {
+        match self {
+            Arg::Bool(true) => vec![Arg::Bool(false)],
+            Arg::Str(s) if !s.is_empty() => vec![
+                Arg::Str(String::new()),
+                Arg::Str(s.chars().take(s.chars().count() / 2).collect()),
+            ],
+            Arg::Int {
+                width,
+                signed,
+                neg,
+                mag,
+            } if !mag.is_zero() => {
+                let int = |neg, mag| Arg::Int {
+                    width: *width,
+                    signed: *signed,
+                    neg,
+                    mag,
+                };
+                let mut out = vec![
+                    int(false, U256::zero()),
+                    int(*neg, *mag / 2),
+                    int(*neg, *mag - 1),
+                ];
+                out.dedup();
+                out
+            }
+            Arg::Seq(items) => {
+                let mut out = vec![Arg::Seq(vec![])];
+                for i in 0..items.len() {
+                    let mut fewer = items.clone();
+                    fewer.remove(i);
+                    out.push(Arg::Seq(fewer));
+                }
+                out.extend(shrink_each(items).into_iter().map(Arg::Seq));
+                out
+            }
+            Arg::Fields(items) => shrink_each(items).into_iter().map(Arg::Fields).collect(),
+            Arg::Variant(index, fields) => shrink_each(fields)
+                .into_iter()
+                .map(|f| Arg::Variant(*index, f))
+                .collect(),
+            _ => vec![],
+        }
+    }
+}
+
+/// every list obtained by shrinking exactly one element
+fn shrink_each(items: &[Arg]) -> Vec<Vec<Arg>> {
+    let mut out = vec![];
+
+    for (i, item) in items.iter().enumerate() {
+        for smaller in item.shrink() {
+            let mut next = items.to_vec();
+            next[i] = smaller;
+            out.push(next);
+        }
+    }
+
+    out
+}
+
+impl fmt::Display for Arg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |f: &mut fmt::Formatter<'_>, items: &[Arg]| {
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{item}")?;
+            }
+            Ok(())
+        };
+
+        match self {
+            Arg::Bool(b) => write!(f, "{b}"),
+            Arg::Char(c) => write!(f, "{c:?}"),
+            Arg::Str(s) => write!(f, "{s:?}"),
+            Arg::Int { neg, mag, .. } => write!(f, "{}{mag}", if *neg { "-" } else { "" }),
+            Arg::AccountId(a) => write!(f, "{}", AccountId32::from(*a)),
+            Arg::Seq(items) => {
+                write!(f, "[")?;
+                list(f, items)?;
+                write!(f, "]")
+            }
+            Arg::Fields(items) => {
+                write!(f, "(")?;
+                list(f, items)?;
+                write!(f, ")")
+            }
+            Arg::Variant(i, items) => {
+                write!(f, "#{i}(")?;
+                list(f, items)?;
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+fn int_bounds(width: usize, signed: bool) -> (U256, U256) {
+    let bits = width * 8;
+    let max = |bits: usize| {
+        if bits >= 256 {
+            U256::MAX
+        } else {
+            (U256::one() << bits) - 1
+        }
+    };
+
+    if signed {
+        // (largest positive, largest negative magnitude)
+        (max(bits - 1), max(bits - 1) + 1)
+    } else {
+        (max(bits), U256::zero())
+    }
+}
+
+fn gen_int(rng: &mut impl Rng, width: usize, signed: bool) -> Arg {
+    let (pos, negm) = int_bounds(width, signed);
+
+    let (neg, mag) = if rng.gen_bool(0.5) {
+        let mut edges = vec![
+            (false, U256::zero()),
+            (false, U256::one()),
+            (false, pos),
+            (false, pos - 1),
+        ];
+        if signed {
+            edges.extend([(true, U256::one()), (true, negm), (true, negm - 1)]);
+        }
+        *edges.choose(rng).unwrap()
+    } else {
+        let mut le = [0_u8; 32];
+        rng.fill(&mut le[..width]);
+        let raw = U256::from_little_endian(&le);
+
+        if signed && raw > pos {
+            // reinterpret the top half of the range as negative numbers
+            let (mask, _) = int_bounds(width, false);
+            (true, (!raw).overflowing_add(U256::one()).0 & mask)
+        } else {
+            (false, raw)
+        }
+    };
+
+    Arg::Int {
+        width,
+        signed,
+        neg,
+        mag,
+    }
+}
+
+/// Generates random values of registry types.
+pub struct Generator<'a> {
+    registry: &'a PortableRegistry,
+    accounts: Vec<[u8; 32]>,
+}
+
+impl<'a> Generator<'a> {
+    pub fn new(registry: &'a PortableRegistry) -> Self {
+        let accounts = AccountKeyring::iter().map(|k| k.to_raw_public()).collect();
+
+        Self { registry, accounts }
+    }
+
+    pub fn generate(&self, rng: &mut impl Rng, ty: u32) -> anyhow::Result<Arg> {
+        let resolved = self
+            .registry
+            .resolve(ty)
+            .ok_or_else(|| anyhow!("type {ty} not found in registry"))?;
+
+        if resolved.path().segments().last().map(String::as_str) == Some("AccountId") {
+            return Ok(Arg::AccountId(*self.accounts.choose(rng).unwrap()));
+        }
+
+        Ok(match resolved.type_def() {
+            TypeDef::Composite(c) => Arg::Fields(
+                c.fields()
+                    .iter()
+                    .map(|f| self.generate(rng, f.ty().id()))
+                    .collect::<anyhow::Result<_>>()?,
+            ),
+            TypeDef::Variant(v) => {
+                let variant = v
+                    .variants()
+                    .choose(rng)
+                    .ok_or_else(|| anyhow!("enum {ty} has no variants"))?;
+
+                Arg::Variant(
+                    variant.index(),
+                    variant
+                        .fields()
+                        .iter()
+                        .map(|f| self.generate(rng, f.ty().id()))
+                        .collect::<anyhow::Result<_>>()?,
+                )
+            }
+            TypeDef::Sequence(s) => Arg::Seq(
+                (0..rng.gen_range(0..=MAX_SEQ_LEN))
+                    .map(|_| self.generate(rng, s.type_param().id()))
+                    .collect::<anyhow::Result<_>>()?,
+            ),
+            TypeDef::Array(a) => Arg::Fields(
+                (0..a.len())
+                    .map(|_| self.generate(rng, a.type_param().id()))
+                    .collect::<anyhow::Result<_>>()?,
+            ),
+            TypeDef::Tuple(t) => Arg::Fields(
+                t.fields()
+                    .iter()
+                    .map(|f| self.generate(rng, f.id()))
+                    .collect::<anyhow::Result<_>>()?,
+            ),
+            TypeDef::Primitive(p) => match p {
+                TypeDefPrimitive::Bool => Arg::Bool(rng.gen()),
+                TypeDefPrimitive::Char => Arg::Char(rng.gen_range('!'..='~')),
+                TypeDefPrimitive::Str => Arg::Str(
+                    (0..rng.gen_range(0..=MAX_STR_LEN))
+                        .map(|_| rng.gen_range(' '..='~'))
+                        .collect(),
+                ),
+                TypeDefPrimitive::U8 => gen_int(rng, 1, false),
+                TypeDefPrimitive::U16 => gen_int(rng, 2, false),
+                TypeDefPrimitive::U32 => gen_int(rng, 4, false),
+                TypeDefPrimitive::U64 => gen_int(rng, 8, false),
+                TypeDefPrimitive::U128 => gen_int(rng, 16, false),
+                TypeDefPrimitive::U256 => gen_int(rng, 32, false),
+                TypeDefPrimitive::I8 => gen_int(rng, 1, true),
+                TypeDefPrimitive::I16 => gen_int(rng, 2, true),
+                TypeDefPrimitive::I32 => gen_int(rng, 4, true),
+                TypeDefPrimitive::I64 => gen_int(rng, 8, true),
+                TypeDefPrimitive::I128 => gen_int(rng, 16, true),
+                TypeDefPrimitive::I256 => gen_int(rng, 32, true),
+            },
+            TypeDef::Compact(_) => return Err(anyhow!("compact arguments are not supported")),
+            TypeDef::BitSequence(_) => {
+                return Err(anyhow!("bit sequence arguments are not supported"))
+            }
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Returned(Vec<u8>),
+    Reverted(Vec<u8>),
+    Trapped,
+    /// any other dispatch error, e.g. running out of gas
+    Failed(String),
+}
+
+/// A minimal input that trapped unexpectedly.
+#[derive(Debug)]
+pub struct Failure {
+    pub message: String,
+    pub args: Vec<Arg>,
+    pub outcome: Outcome,
+    /// the seed of the run that found the input
+    pub seed: u64,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}(", self.message)?;
+        for (i, a) in self.args.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{a}")?;
+        }
+        write!(
+            f,
+            ") -> {:?} (seed {}, rerun with FUZZ_SEED={})",
+            self.outcome, self.seed, self.seed
+        )
+    }
+}
+
+impl std::error::Error for Failure {}
+
+/// Fuzzes the messages of one deployed contract.
+pub struct Fuzzer<'a> {
+    project: &'a InkProject,
+    address: AccountId32,
+    runs: usize,
+    seed: u64,
+    expect_trap: Box<dyn Fn(&str, &[Arg]) -> bool + Send + Sync + 'a>,
+    expect_failure: Box<dyn Fn(&str, &[Arg]) -> bool + Send + Sync + 'a>,
+}
+
+impl<'a> Fuzzer<'a> {
+    pub fn new(project: &'a InkProject, address: AccountId32) -> Self {
+        Self {
+            project,
+            address,
+            runs: 32,
+            seed: seed_from_env("FUZZ_SEED"),
+            expect_trap: Box::new(|_, _| false),
+            expect_failure: Box::new(|_, _| false),
+        }
+    }
+
+    pub fn runs(mut self, runs: usize) -> Self {
+        self.runs = runs;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Declare when a trap is the documented behaviour of a message, e.g. division by zero.
+    pub fn expect_trap(mut self, f: impl Fn(&str, &[Arg]) -> bool + Send + Sync + 'a) -> Self {
+        self.expect_trap = Box::new(f);
+        self
+    }
+
+    /// Declare when another dispatch error is the documented behaviour of a message, e.g.
+    /// running out of gas.
+    pub fn expect_failure(mut self, f: impl Fn(&str, &[Arg]) -> bool + Send + Sync + 'a) -> Self {
+        self.expect_failure = Box::new(f);
+        self
+    }
+
+    async fn run(&self, api: &API, selector: &[u8], args: &[Arg]) -> anyhow::Result<Outcome> {
+        let mut input = selector.to_vec();
+        args.iter().for_each(|a| a.encode_to(&mut input));
+
+        let rv = dry_run_call(api, &AccountKeyring::Alice, self.address.clone(), 0, input).await?;
+
+        Ok(match rv.result {
+            Ok(r) if r.did_revert() => Outcome::Reverted(r.data.to_vec()),
+            Ok(r) => Outcome::Returned(r.data.to_vec()),
+            Err(e) => {
+                let details = match &e {
+                    DispatchError::Module(m) => api
+                        .metadata()
+                        .error(m.index, m.error)
+                        .map(|d| format!("{d:?}"))
+                        .unwrap_or_else(|_| format!("{e:?}")),
+                    _ => format!("{e:?}"),
+                };
+
+                if details.contains("ContractTrapped") {
+                    Outcome::Trapped
+                } else {
+                    Outcome::Failed(details)
+                }
+            }
+        })
+    }
+
+    fn is_failure(&self, message: &str, args: &[Arg], outcome: &Outcome) -> bool {
+        match outcome {
+            Outcome::Trapped => !(self.expect_trap)(message, args),
+            Outcome::Failed(_) => !(self.expect_failure)(message, args),
+            _ => false,
+        }
+    }
+
+    /// Fuzz one message, returning the shrunk input of the first unexpected trap or failure.
+    pub async fn message(&self, api: &API, message: &str) -> anyhow::Result<()> {
+        let spec = self
+            .project
+            .spec()
+            .messages()
+            .iter()
+            .find(|m| m.label() == message)
+            .ok_or_else(|| anyhow!("no message named {message}"))?;
+        let selector = spec.selector().to_bytes().to_vec();
+        let types = spec
+            .args()
+            .iter()
+            .map(|a| a.ty().ty().id())
+            .collect::<Vec<_>>();
+
+        let generator = Generator::new(self.project.registry());
+        let mut rng = StdRng::seed_from_u64(self.seed);
+
+        for _ in 0..self.runs {
+            let args = types
+                .iter()
+                .map(|ty| generator.generate(&mut rng, *ty))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+
+            let outcome = self.run(api, &selector, &args).await?;
+
+            if self.is_failure(message, &args, &outcome) {
+                let args = self.shrink(api, message, &selector, args).await?;
+
+                return Err(Failure {
+                    message: message.to_string(),
+                    args,
+                    outcome,
+                    seed: self.seed,
+                }
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fuzz every message of the contract except `skip`.
+    pub async fn all_messages(&self, api: &API, skip: &[&str]) -> anyhow::Result<()> {
+        for m in self.project.spec().messages() {
+            if !skip.contains(&m.label().as_str()) {
+                self.message(api, m.label()).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn shrink(
+        &self,
+        api: &API,
+        message: &str,
+        selector: &[u8],
+        mut args: Vec<Arg>,
+    ) -> anyhow::Result<Vec<Arg>> {
+        let mut steps = 0;
+
+        'outer: while steps < MAX_SHRINK_STEPS {
+            for candidate in shrink_each(&args) {
+                steps += 1;
+
+                let outcome = self.run(api, selector, &candidate).await?;
+                if self.is_failure(message, &candidate, &outcome) {
+                    args = candidate;
+                    continue 'outer;
+                }
+
+                if steps >= MAX_SHRINK_STEPS {
+                    break 'outer;
+                }
+            }
+
+            break;
+        }
+
+        Ok(args)
+    }
+}
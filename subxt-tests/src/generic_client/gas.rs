@@ -0,0 +1,432 @@
+//! gas profiling of every deploy and message sent through `generic_client`
+//!
+//! Enabled by pointing `GAS_REPORT` at a directory. Every `DeployContract`, `WriteContract` and
+//! `ReadContract` is then dry-run first and the resulting `gas_consumed`, `gas_required` and
+//! storage deposit are recorded against the contract and message they belong to. Contracts are
+//! known by the code hash of every metadata bundle loaded during the run, anything else is
+//! reported by code hash and selector. The aggregated report is rewritten after each record as
+//! `gas_report.json` and `gas_report.csv`, so it is complete whenever the run stops.
+//!
+//! With `GAS_BASELINE` pointing at a previous `gas_report.json` (see `gas-baseline.json`), the
+//! per-message diff table against the baseline is kept up to date in `gas_diff.txt` as well.
+//! Recording never fails a call: once the suite is done, the ignored `regressions` test compares
+//! the whole report with the baseline and fails if any `gas_consumed` or storage deposit grew more
+//! than `GAS_THRESHOLD` percent (default 5) beyond it, printing the full table. Messages missing
+//! from the baseline are reported as new and never fail, an empty baseline is an error.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Write as _},
+    path::PathBuf,
+    sync::Mutex,
+};
+
+use contract_metadata::ContractMetadata;
+use ink_metadata::InkProject;
+use once_cell::sync::Lazy;
+use pallet_contracts_primitives::StorageDeposit;
+
+pub const REPORT_JSON: &str = "gas_report.json";
+pub const REPORT_CSV: &str = "gas_report.csv";
+pub const DIFF_TABLE: &str = "gas_diff.txt";
+const DEFAULT_THRESHOLD: f64 = 5.0;
+
+struct KnownContract {
+    name: String,
+    wasm_size: usize,
+    selectors: HashMap<[u8; 4], String>,
+}
+
+/// Aggregated measurements of one entry point.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Measurement {
+    pub calls: u64,
+    pub gas_consumed_min: u64,
+    pub gas_consumed_max: u64,
+    pub gas_consumed_total: u128,
+    pub gas_required_max: u64,
+    /// largest storage deposit charged, negative if every run was a refund
+    pub storage_deposit_max: i128,
+    pub wasm_size: usize,
+}
+
+impl Measurement {
+    pub fn gas_consumed_avg(&self) -> u64 {
+        (self.gas_consumed_total / self.calls.max(1) as u128) as u64
+    }
+
+    fn add(&mut self, gas_consumed: u64, gas_required: u64, storage_deposit: i128) {
+        if self.calls == 0 {
+            self.gas_consumed_min = gas_consumed;
+            self.storage_deposit_max = storage_deposit;
+        }
+
+        self.calls += 1;
+        self.gas_consumed_min = self.gas_consumed_min.min(gas_consumed);
+        self.gas_consumed_max = self.gas_consumed_max.max(gas_consumed);
+        self.gas_consumed_total += gas_consumed as u128;
+        self.gas_required_max = self.gas_required_max.max(gas_required);
+        self.storage_deposit_max = self.storage_deposit_max.max(storage_deposit);
+    }
+}
+
+#[derive(Default)]
+struct Profile {
+    known: HashMap<[u8; 32], KnownContract>,
+    /// (contract, entry point) -> measurement
+    records: BTreeMap<(String, String), Measurement>,
+}
+
+static PROFILE: Lazy<Mutex<Profile>> = Lazy::new(Default::default);
+
+static BASELINE: Lazy<Option<serde_json::Value>> = Lazy::new(|| {
+    let path = std::env::var_os("GAS_BASELINE")?;
+    let raw = std::fs::read(&path).expect("unable to read gas baseline");
+
+    Some(serde_json::from_slice(&raw).expect("malformed gas baseline"))
+});
+
+fn threshold() -> f64 {
+    std::env::var("GAS_THRESHOLD")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_THRESHOLD)
+}
+
+fn report_dir() -> Option<PathBuf> {
+    std::env::var_os("GAS_REPORT").map(PathBuf::from)
+}
+
+pub fn enabled() -> bool {
+    report_dir().is_some()
+}
+
+/// remember the name and message labels of a loaded bundle
+pub(crate) fn register(contract: &ContractMetadata, project: &InkProject) {
+    let spec = project.spec();
+
+    let selectors = spec
+        .constructors()
+        .iter()
+        .map(|c| (*c.selector().to_bytes(), c.label().clone()))
+        .chain(
+            spec.messages()
+                .iter()
+                .map(|m| (*m.selector().to_bytes(), m.label().clone())),
+        )
+        .collect();
+
+    let known = KnownContract {
+        name: contract.contract.name.clone(),
+        wasm_size: contract.source.wasm.as_ref().map_or(0, |w| w.0.len()),
+        selectors,
+    };
+
+    PROFILE
+        .lock()
+        .unwrap()
+        .known
+        .insert(contract.source.hash.0, known);
+}
+
+fn deposit(d: &StorageDeposit<u128>) -> i128 {
+    match d {
+        StorageDeposit::Charge(v) => *v as i128,
+        StorageDeposit::Refund(v) => -(*v as i128),
+    }
+}
+
+/// record one dry-run of `selector` on the contract with `code_hash`
+pub(crate) fn record(
+    code_hash: [u8; 32],
+    selector: &[u8],
+    gas_consumed: u64,
+    gas_required: u64,
+    storage_deposit: &StorageDeposit<u128>,
+) -> anyhow::Result<()> {
+    let dir = match report_dir() {
+        Some(dir) => dir,
+        None => return Ok(()),
+    };
+
+    let mut profile = PROFILE.lock().unwrap();
+
+    let prefix = selector.get(..4).and_then(|s| <[u8; 4]>::try_from(s).ok());
+    let (contract, message, wasm_size) = match profile.known.get(&code_hash) {
+        Some(k) => (
+            k.name.clone(),
+            prefix
+                .and_then(|s| k.selectors.get(&s).cloned())
+                .unwrap_or_else(|| format!("0x{}", hex::encode(prefix.unwrap_or_default()))),
+            k.wasm_size,
+        ),
+        None => (
+            format!("0x{}", hex::encode(code_hash)),
+            format!("0x{}", hex::encode(prefix.unwrap_or_default())),
+            0,
+        ),
+    };
+
+    let m = profile
+        .records
+        .entry((contract.clone(), message.clone()))
+        .or_default();
+    m.wasm_size = wasm_size;
+    m.add(gas_consumed, gas_required, deposit(storage_deposit));
+
+    write_report(&dir, &profile.records)?;
+
+    if let Some(baseline) = BASELINE.as_ref() {
+        let table = compare(&profile.records, baseline, threshold());
+        std::fs::write(dir.join(DIFF_TABLE), table.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Compare the report written so far in `GAS_REPORT` with `GAS_BASELINE`, failing with the full
+/// diff table if anything regressed.
+pub fn check_regressions() -> anyhow::Result<()> {
+    let dir = report_dir().ok_or_else(|| anyhow::anyhow!("GAS_REPORT is not set"))?;
+    let baseline = BASELINE
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("GAS_BASELINE is not set"))?;
+
+    if baseline.as_object().map_or(true, |b| b.is_empty()) {
+        return Err(anyhow::anyhow!(
+            "gas baseline is empty, commit the gas_report.json of a full run as the baseline"
+        ));
+    }
+
+    let report = serde_json::from_slice(&std::fs::read(dir.join(REPORT_JSON))?)?;
+    let table = compare(&from_json(&report), baseline, threshold());
+    std::fs::write(dir.join(DIFF_TABLE), table.to_string())?;
+
+    let regressions = table.regressions().count();
+    if regressions > 0 {
+        return Err(anyhow::anyhow!(
+            "{regressions} gas regression(s) beyond {}%:\n{table}",
+            table.threshold
+        ));
+    }
+
+    println!("{table}");
+
+    Ok(())
+}
+
+/// Run after the suite against the same `GAS_REPORT` directory:
+/// `cargo test generic_client::gas::regressions -- --exact --ignored`
+#[test]
+#[ignore]
+fn regressions() -> anyhow::Result<()> {
+    check_regressions()
+}
+
+fn write_report(
+    dir: &std::path::Path,
+    records: &BTreeMap<(String, String), Measurement>,
+) -> anyhow::Result<()> {
+    std::fs::create_dir_all(dir)?;
+
+    std::fs::write(
+        dir.join(REPORT_JSON),
+        serde_json::to_string_pretty(&to_json(records))?,
+    )?;
+
+    let mut csv = String::from(
+        "contract,message,calls,gas_consumed_min,gas_consumed_avg,gas_consumed_max,gas_required_max,storage_deposit_max,wasm_size\n",
+    );
+    for ((contract, message), m) in records {
+        writeln!(
+            csv,
+            "{contract},{message},{},{},{},{},{},{},{}",
+            m.calls,
+            m.gas_consumed_min,
+            m.gas_consumed_avg(),
+            m.gas_consumed_max,
+            m.gas_required_max,
+            m.storage_deposit_max,
+            m.wasm_size
+        )?;
+    }
+    std::fs::write(dir.join(REPORT_CSV), csv)?;
+
+    Ok(())
+}
+
+/// `{ contract: { message: { .. } } }`
+pub fn to_json(records: &BTreeMap<(String, String), Measurement>) -> serde_json::Value {
+    let mut out = serde_json::Map::new();
+
+    for ((contract, message), m) in records {
+        let entry = out
+            .entry(contract.clone())
+            .or_insert_with(|| serde_json::Value::Object(Default::default()));
+
+        if let serde_json::Value::Object(messages) = entry {
+            messages.insert(
+                message.clone(),
+                serde_json::json!({
+                    "calls": m.calls,
+                    "gas_consumed_min": m.gas_consumed_min,
+                    "gas_consumed_avg": m.gas_consumed_avg(),
+                    "gas_consumed_max": m.gas_consumed_max,
+                    "gas_required_max": m.gas_required_max,
+                    // i128 is not representable in JSON numbers
+                    "storage_deposit_max": m.storage_deposit_max.to_string(),
+                    "wasm_size": m.wasm_size,
+                }),
+            );
+        }
+    }
+
+    serde_json::Value::Object(out)
+}
+
+/// Read back measurements written by `to_json`.
+pub fn from_json(report: &serde_json::Value) -> BTreeMap<(String, String), Measurement> {
+    let mut records = BTreeMap::new();
+
+    for (contract, messages) in report.as_object().into_iter().flatten() {
+        for (message, m) in messages.as_object().into_iter().flatten() {
+            let u64_of = |k: &str| m.get(k).and_then(|v| v.as_u64()).unwrap_or_default();
+            let calls = u64_of("calls");
+
+            let measurement = Measurement {
+                calls,
+                gas_consumed_min: u64_of("gas_consumed_min"),
+                gas_consumed_max: u64_of("gas_consumed_max"),
+                gas_consumed_total: u64_of("gas_consumed_avg") as u128 * calls as u128,
+                gas_required_max: u64_of("gas_required_max"),
+                storage_deposit_max: m
+                    .get("storage_deposit_max")
+                    .and_then(|v| v.as_str())
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or_default(),
+                wasm_size: u64_of("wasm_size") as usize,
+            };
+
+            records.insert((contract.clone(), message.clone()), measurement);
+        }
+    }
+
+    records
+}
+
+/// One entry point measured against the baseline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffRow {
+    pub contract: String,
+    pub message: String,
+    /// baseline `(gas_consumed_max, storage_deposit_max)`, `None` for a new message
+    pub baseline: Option<(u64, i128)>,
+    pub current: (u64, i128),
+    pub regressed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffTable {
+    pub threshold: f64,
+    pub rows: Vec<DiffRow>,
+}
+
+impl DiffTable {
+    pub fn regressions(&self) -> impl Iterator<Item = &DiffRow> {
+        self.rows.iter().filter(|r| r.regressed)
+    }
+}
+
+fn change(base: i128, now: i128) -> String {
+    if base == 0 {
+        return if now == 0 { "0%".into() } else { "n/a".into() };
+    }
+
+    format!("{:+.2}%", (now - base) as f64 * 100.0 / base.abs() as f64)
+}
+
+impl fmt::Display for DiffTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<24} {:<28} {:>14} {:>14} {:>9} {:>14} {:>14} {:>9}",
+            "contract",
+            "message",
+            "gas (base)",
+            "gas (now)",
+            "change",
+            "deposit (base)",
+            "deposit (now)",
+            "change"
+        )?;
+
+        for r in &self.rows {
+            let mark = if r.regressed { " !" } else { "" };
+
+            match r.baseline {
+                Some((gas, dep)) => writeln!(
+                    f,
+                    "{:<24} {:<28} {:>14} {:>14} {:>9} {:>14} {:>14} {:>9}{mark}",
+                    r.contract,
+                    r.message,
+                    gas,
+                    r.current.0,
+                    change(gas as i128, r.current.0 as i128),
+                    dep,
+                    r.current.1,
+                    change(dep, r.current.1)
+                )?,
+                None => writeln!(
+                    f,
+                    "{:<24} {:<28} {:>14} {:>14} {:>9} {:>14} {:>14} {:>9}",
+                    r.contract, r.message, "-", r.current.0, "new", "-", r.current.1, "new"
+                )?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn exceeds(base: i128, now: i128, threshold: f64) -> bool {
+    now > base && (now - base) as f64 * 100.0 > base.abs() as f64 * threshold
+}
+
+/// Compare measurements with a baseline in the `gas_report.json` format.
+pub fn compare(
+    records: &BTreeMap<(String, String), Measurement>,
+    baseline: &serde_json::Value,
+    threshold: f64,
+) -> DiffTable {
+    let rows = records
+        .iter()
+        .map(|((contract, message), m)| {
+            let current = (m.gas_consumed_max, m.storage_deposit_max);
+
+            let baseline = baseline
+                .get(contract)
+                .and_then(|c| c.get(message))
+                .and_then(|b| {
+                    let gas = b.get("gas_consumed_max")?.as_u64()?;
+                    let dep = b.get("storage_deposit_max")?.as_str()?.parse().ok()?;
+
+                    Some((gas, dep))
+                });
+
+            let regressed = baseline.map_or(false, |(gas, dep)| {
+                exceeds(gas as i128, current.0 as i128, threshold)
+                    || exceeds(dep, current.1, threshold)
+            });
+
+            DiffRow {
+                contract: contract.clone(),
+                message: message.clone(),
+                baseline,
+                current,
+                regressed,
+            }
+        })
+        .collect();
+
+    DiffTable { threshold, rows }
+}
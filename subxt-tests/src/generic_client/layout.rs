@@ -0,0 +1,590 @@
+//! ink! storage layout walker
+//!
+//! Derives the raw storage keys of contract fields from the `InkProject` storage layout, so
+//! contract state can be read through `ContractsApi_get_storage` without hand-computed keys.
+
+use std::fmt;
+
+use anyhow::anyhow;
+use ink_metadata::{
+    layout::{CryptoHasher, Layout},
+    InkProject,
+};
+use parity_scale_codec::{Compact, Decode, Encode};
+use scale_info::{form::PortableForm, Field, PortableRegistry, TypeDef, TypeDefPrimitive};
+use sp_core::{blake2_256, keccak_256, sha2_256, U256};
+
+/// A resolved storage cell: the raw key and the registry type of the value stored under it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageCell {
+    pub key: [u8; 32],
+    pub ty: u32,
+}
+
+enum Segment<'a> {
+    Field(&'a str),
+    Index(u64),
+}
+
+/// parse `a.b[2].c` into field and index segments
+fn parse_path(path: &str) -> anyhow::Result<Vec<Segment<'_>>> {
+    let mut segments = Vec::new();
+
+    for part in path.split('.') {
+        let (name, mut rest) = part.split_at(part.find('[').unwrap_or(part.len()));
+
+        if name.is_empty() {
+            return Err(anyhow!("empty field name in `{path}`"));
+        }
+
+        segments.push(Segment::Field(name));
+
+        while !rest.is_empty() {
+            let end = rest
+                .find(']')
+                .filter(|_| rest.starts_with('['))
+                .ok_or_else(|| anyhow!("malformed index in `{path}`"))?;
+
+            segments.push(Segment::Index(rest[1..end].trim().parse()?));
+            rest = &rest[end + 1..];
+        }
+    }
+
+    Ok(segments)
+}
+
+/// ink! keys behave like little-endian 256 bit integers when advanced by a cell offset
+fn offset_key(key: &[u8; 32], offset: U256) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    U256::from_little_endian(key)
+        .overflowing_add(offset)
+        .0
+        .to_little_endian(&mut out);
+    out
+}
+
+pub struct InkLayout<'a> {
+    project: &'a InkProject,
+}
+
+impl<'a> InkLayout<'a> {
+    pub fn new(project: &'a InkProject) -> Self {
+        Self { project }
+    }
+
+    pub fn registry(&self) -> &'a PortableRegistry {
+        self.project.registry()
+    }
+
+    /// walk the layout along `path`, accumulating the cell offset introduced by array indices
+    fn resolve(&self, path: &str) -> anyhow::Result<(&'a Layout<PortableForm>, U256)> {
+        let mut layout = self.project.layout();
+        let mut offset = U256::zero();
+
+        for segment in parse_path(path)? {
+            layout = match (segment, layout) {
+                (Segment::Field(name), Layout::Struct(s)) => s
+                    .fields()
+                    .iter()
+                    .find(|f| f.name().map(String::as_str) == Some(name))
+                    .map(|f| f.layout())
+                    .ok_or_else(|| anyhow!("no field `{name}` in `{path}`"))?,
+                (Segment::Index(i), Layout::Array(a)) => {
+                    if i >= a.len() as u64 {
+                        return Err(anyhow!("index {i} out of bounds in `{path}`"));
+                    }
+
+                    offset += U256::from(i) * U256::from(a.cells_per_elem());
+                    a.layout()
+                }
+                (Segment::Field(name), _) => {
+                    return Err(anyhow!("`{name}` is not a struct field in `{path}`"))
+                }
+                (Segment::Index(i), _) => {
+                    return Err(anyhow!("[{i}] does not index an array in `{path}`"))
+                }
+            };
+        }
+
+        Ok((layout, offset))
+    }
+
+    /// Locate the cell holding the field at `path`, e.g. `token_id` or `inner.values[2]`.
+    pub fn field(&self, path: &str) -> anyhow::Result<StorageCell> {
+        match self.resolve(path)? {
+            (Layout::Cell(cell), offset) => Ok(StorageCell {
+                key: offset_key(cell.key().key(), offset),
+                ty: cell.ty().id(),
+            }),
+            _ => Err(anyhow!("`{path}` does not point to a single cell")),
+        }
+    }
+
+    /// Locate the cell holding `key` of the `Mapping` (or hashing layout) at `path`.
+    pub fn mapping_entry(&self, path: &str, key: &impl Encode) -> anyhow::Result<StorageCell> {
+        match self.resolve(path)? {
+            (Layout::Cell(cell), offset) => {
+                let (key_ty, value_ty) = mapping_params(self.registry(), cell.ty().id())
+                    .ok_or_else(|| anyhow!("`{path}` is not a Mapping"))?;
+
+                let encoded = key.encode();
+                check_key_type(self.registry(), key_ty, &encoded)?;
+
+                // ink_storage::Mapping stores entries at blake2x256((offset_key, key))
+                let root = offset_key(cell.key().key(), offset);
+
+                Ok(StorageCell {
+                    key: blake2_256(&[&root[..], &encoded].concat()),
+                    ty: value_ty,
+                })
+            }
+            (Layout::Hash(hash), offset) => {
+                let value = match hash.layout() {
+                    Layout::Cell(cell) => cell.ty().id(),
+                    _ => return Err(anyhow!("`{path}` hashes into a non-cell layout")),
+                };
+
+                let strategy = hash.strategy();
+                let root = offset_key(hash.offset().key(), offset);
+                let preimage = [
+                    strategy.prefix(),
+                    &key.encode(),
+                    &root[..],
+                    strategy.postfix(),
+                ]
+                .concat();
+
+                let key = match strategy.hasher() {
+                    CryptoHasher::Blake2x256 => blake2_256(&preimage),
+                    CryptoHasher::Sha2x256 => sha2_256(&preimage),
+                    CryptoHasher::Keccak256 => keccak_256(&preimage),
+                };
+
+                Ok(StorageCell { key, ty: value })
+            }
+            _ => Err(anyhow!(
+                "`{path}` is neither a Mapping nor a hashing layout"
+            )),
+        }
+    }
+
+    /// Every cell reachable from the root layout, labelled by its field path.
+    ///
+    /// Mapping cells are reported with their root key; individual entries can't be enumerated.
+    pub fn cells(&self) -> Vec<(String, StorageCell)> {
+        let mut out = Vec::new();
+        collect_cells(self.project.layout(), String::new(), U256::zero(), &mut out);
+        out
+    }
+}
+
+fn collect_cells(
+    layout: &Layout<PortableForm>,
+    path: String,
+    offset: U256,
+    out: &mut Vec<(String, StorageCell)>,
+) {
+    match layout {
+        Layout::Cell(cell) => out.push((
+            path,
+            StorageCell {
+                key: offset_key(cell.key().key(), offset),
+                ty: cell.ty().id(),
+            },
+        )),
+        Layout::Struct(s) => {
+            for (i, f) in s.fields().iter().enumerate() {
+                let name = f.name().cloned().unwrap_or_else(|| i.to_string());
+                let path = if path.is_empty() {
+                    name
+                } else {
+                    format!("{path}.{name}")
+                };
+
+                collect_cells(f.layout(), path, offset, out);
+            }
+        }
+        Layout::Array(a) => {
+            for i in 0..a.len() {
+                let offset = offset + U256::from(i) * U256::from(a.cells_per_elem());
+                collect_cells(a.layout(), format!("{path}[{i}]"), offset, out);
+            }
+        }
+        Layout::Enum(e) => {
+            for (i, variant) in e.variants().values().enumerate() {
+                for f in variant.fields() {
+                    let name = f.name().cloned().unwrap_or_default();
+                    collect_cells(f.layout(), format!("{path}::{i}.{name}"), offset, out);
+                }
+            }
+        }
+        // entries of a hashing layout can't be enumerated without their keys
+        Layout::Hash(_) => {}
+    }
+}
+
+/// type parameters `K` and `V` if `ty` is an `ink_storage` `Mapping<K, V>`
+fn mapping_params(registry: &PortableRegistry, ty: u32) -> Option<(u32, u32)> {
+    let ty = registry.resolve(ty)?;
+
+    if ty.path().segments().last().map(String::as_str) != Some("Mapping") {
+        return None;
+    }
+
+    let param = |name: &str| {
+        ty.type_params()
+            .iter()
+            .find(|p| p.name() == name)
+            .and_then(|p| p.ty())
+            .map(|t| t.id())
+    };
+
+    Some((param("K")?, param("V")?))
+}
+
+/// make sure the caller-provided key is a valid encoding of the mapping's key type
+fn check_key_type(registry: &PortableRegistry, ty: u32, encoded: &[u8]) -> anyhow::Result<()> {
+    let input = &mut &encoded[..];
+
+    decode_value(registry, ty, input)?;
+
+    if input.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!("mapping key has {} trailing bytes", input.len()))
+    }
+}
+
+/// A SCALE value decoded against the contract's type registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decoded {
+    Bool(bool),
+    Char(char),
+    Str(String),
+    UInt(U256),
+    Int(i128),
+    /// byte sequences, byte arrays and 256 bit signed integers
+    Bytes(Vec<u8>),
+    Seq(Vec<Decoded>),
+    Composite(Vec<(Option<String>, Decoded)>),
+    Variant(String, Vec<(Option<String>, Decoded)>),
+}
+
+fn fmt_fields(f: &mut fmt::Formatter<'_>, fields: &[(Option<String>, Decoded)]) -> fmt::Result {
+    for (i, (name, value)) in fields.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+
+        match name {
+            Some(name) => write!(f, "{name}: {value}")?,
+            None => write!(f, "{value}")?,
+        }
+    }
+
+    Ok(())
+}
+
+impl fmt::Display for Decoded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Decoded::Bool(v) => write!(f, "{v}"),
+            Decoded::Char(v) => write!(f, "{v:?}"),
+            Decoded::Str(v) => write!(f, "{v:?}"),
+            Decoded::UInt(v) => write!(f, "{v}"),
+            Decoded::Int(v) => write!(f, "{v}"),
+            Decoded::Bytes(v) => write!(f, "0x{}", hex::encode(v)),
+            Decoded::Seq(v) => {
+                write!(f, "[")?;
+                for (i, e) in v.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{e}")?;
+                }
+                write!(f, "]")
+            }
+            Decoded::Composite(fields) => {
+                write!(f, "{{ ")?;
+                fmt_fields(f, fields)?;
+                write!(f, " }}")
+            }
+            Decoded::Variant(name, fields) if fields.is_empty() => write!(f, "{name}"),
+            Decoded::Variant(name, fields) => {
+                write!(f, "{name}(")?;
+                fmt_fields(f, fields)?;
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+fn decode_fields(
+    registry: &PortableRegistry,
+    fields: &[Field<PortableForm>],
+    input: &mut &[u8],
+) -> anyhow::Result<Vec<(Option<String>, Decoded)>> {
+    fields
+        .iter()
+        .map(|f| {
+            Ok((
+                f.name().cloned(),
+                decode_value(registry, f.ty().id(), input)?,
+            ))
+        })
+        .collect()
+}
+
+fn decode_elements(
+    registry: &PortableRegistry,
+    ty: u32,
+    len: usize,
+    input: &mut &[u8],
+) -> anyhow::Result<Decoded> {
+    let is_byte = matches!(
+        registry.resolve(ty).map(|t| t.type_def()),
+        Some(TypeDef::Primitive(TypeDefPrimitive::U8))
+    );
+
+    if is_byte {
+        if input.len() < len {
+            return Err(anyhow!("expected {len} bytes, found {}", input.len()));
+        }
+
+        let (bytes, rest) = input.split_at(len);
+        *input = rest;
+
+        return Ok(Decoded::Bytes(bytes.to_vec()));
+    }
+
+    (0..len)
+        .map(|_| decode_value(registry, ty, input))
+        .collect::<anyhow::Result<_>>()
+        .map(Decoded::Seq)
+}
+
+fn decode_primitive(p: &TypeDefPrimitive, input: &mut &[u8]) -> anyhow::Result<Decoded> {
+    Ok(match p {
+        TypeDefPrimitive::Bool => Decoded::Bool(bool::decode(input)?),
+        TypeDefPrimitive::Char => Decoded::Char(
+            char::from_u32(u32::decode(input)?).ok_or_else(|| anyhow!("invalid char"))?,
+        ),
+        TypeDefPrimitive::Str => Decoded::Str(String::decode(input)?),
+        TypeDefPrimitive::U8 => Decoded::UInt(u8::decode(input)?.into()),
+        TypeDefPrimitive::U16 => Decoded::UInt(u16::decode(input)?.into()),
+        TypeDefPrimitive::U32 => Decoded::UInt(u32::decode(input)?.into()),
+        TypeDefPrimitive::U64 => Decoded::UInt(u64::decode(input)?.into()),
+        TypeDefPrimitive::U128 => Decoded::UInt(u128::decode(input)?.into()),
+        TypeDefPrimitive::U256 => Decoded::UInt(U256::decode(input)?),
+        TypeDefPrimitive::I8 => Decoded::Int(i8::decode(input)?.into()),
+        TypeDefPrimitive::I16 => Decoded::Int(i16::decode(input)?.into()),
+        TypeDefPrimitive::I32 => Decoded::Int(i32::decode(input)?.into()),
+        TypeDefPrimitive::I64 => Decoded::Int(i64::decode(input)?.into()),
+        TypeDefPrimitive::I128 => Decoded::Int(i128::decode(input)?),
+        TypeDefPrimitive::I256 => Decoded::Bytes(<[u8; 32]>::decode(input)?.to_vec()),
+    })
+}
+
+/// Decode a single value of registry type `ty` from `input`.
+pub fn decode_value(
+    registry: &PortableRegistry,
+    ty: u32,
+    input: &mut &[u8],
+) -> anyhow::Result<Decoded> {
+    let ty = registry
+        .resolve(ty)
+        .ok_or_else(|| anyhow!("type {ty} not found in registry"))?;
+
+    match ty.type_def() {
+        TypeDef::Composite(c) => decode_fields(registry, c.fields(), input).map(Decoded::Composite),
+        TypeDef::Variant(v) => {
+            let index = u8::decode(input)?;
+            let variant = v
+                .variants()
+                .iter()
+                .find(|v| v.index() == index)
+                .ok_or_else(|| anyhow!("invalid variant index {index}"))?;
+
+            Ok(Decoded::Variant(
+                variant.name().clone(),
+                decode_fields(registry, variant.fields(), input)?,
+            ))
+        }
+        TypeDef::Sequence(s) => {
+            let len = <Compact<u32>>::decode(input)?.0 as usize;
+            decode_elements(registry, s.type_param().id(), len, input)
+        }
+        TypeDef::Array(a) => {
+            decode_elements(registry, a.type_param().id(), a.len() as usize, input)
+        }
+        TypeDef::Tuple(t) => t
+            .fields()
+            .iter()
+            .map(|f| Ok((None, decode_value(registry, f.id(), input)?)))
+            .collect::<anyhow::Result<_>>()
+            .map(Decoded::Composite),
+        TypeDef::Primitive(p) => decode_primitive(p, input),
+        TypeDef::Compact(_) => Ok(Decoded::UInt(<Compact<u128>>::decode(input)?.0.into())),
+        TypeDef::BitSequence(_) => Err(anyhow!("bit sequences are not supported")),
+    }
+}
+
+/// Decode a whole storage value, rejecting trailing bytes.
+pub fn decode_all(registry: &PortableRegistry, ty: u32, raw: &[u8]) -> anyhow::Result<Decoded> {
+    let input = &mut &raw[..];
+    let value = decode_value(registry, ty, input)?;
+
+    if input.is_empty() {
+        Ok(value)
+    } else {
+        Err(anyhow!("{} trailing bytes after decoding", input.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ink_metadata::MetadataVersioned;
+
+    fn key(first: u8) -> [u8; 32] {
+        let mut key = [0u8; 32];
+        key[0] = first;
+        key
+    }
+
+    /// the layout ink! 3 derives for
+    /// `struct { token_id: u32, values: [u32; 3], balances: Mapping<u32, u128> }`
+    fn project() -> InkProject {
+        let key = |first| format!("0x{}", hex::encode(key(first)));
+
+        let metadata = serde_json::json!({ "V3": {
+            "spec": { "constructors": [], "docs": [], "events": [], "messages": [] },
+            "storage": { "struct": { "fields": [
+                { "name": "token_id", "layout": { "cell": { "key": key(0), "ty": 0 } } },
+                { "name": "values", "layout": { "array": {
+                    "offset": key(1),
+                    "len": 3,
+                    "cellsPerElem": 1,
+                    "layout": { "cell": { "key": key(1), "ty": 0 } },
+                } } },
+                { "name": "balances", "layout": { "cell": { "key": key(4), "ty": 1 } } },
+            ] } },
+            "types": [
+                { "id": 0, "type": { "def": { "primitive": "u32" } } },
+                { "id": 1, "type": {
+                    "path": ["ink_storage", "lazy", "mapping", "Mapping"],
+                    "params": [{ "name": "K", "type": 0 }, { "name": "V", "type": 2 }],
+                    "def": { "composite": { "fields": [
+                        { "name": "offset_key", "type": 3, "typeName": "Key" },
+                    ] } },
+                } },
+                { "id": 2, "type": { "def": { "primitive": "u128" } } },
+                { "id": 3, "type": {
+                    "path": ["ink_primitives", "Key"],
+                    "def": { "composite": { "fields": [{ "type": 4, "typeName": "[u8; 32]" }] } },
+                } },
+                { "id": 4, "type": { "def": { "array": { "len": 32, "type": 5 } } } },
+                { "id": 5, "type": { "def": { "primitive": "u8" } } },
+            ],
+        } });
+
+        match serde_json::from_value(metadata).unwrap() {
+            MetadataVersioned::V3(project) => project,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn parse_paths() {
+        assert!(matches!(
+            parse_path("inner.values[2][10]").unwrap()[..],
+            [
+                Segment::Field("inner"),
+                Segment::Field("values"),
+                Segment::Index(2),
+                Segment::Index(10)
+            ]
+        ));
+
+        for malformed in ["", "a..b", "[1]", "a[1", "a[x]", "a]1["] {
+            assert!(parse_path(malformed).is_err(), "{malformed}");
+        }
+    }
+
+    #[test]
+    fn offset_keys_carry_little_endian() {
+        assert_eq!(offset_key(&key(1), U256::from(2)), key(3));
+
+        let mut carried = key(0);
+        carried[1] = 1;
+        assert_eq!(offset_key(&key(0xff), U256::one()), carried);
+
+        // the key space wraps around
+        assert_eq!(offset_key(&[0xff; 32], U256::one()), key(0));
+    }
+
+    #[test]
+    fn fields_resolve_to_their_cells() {
+        let project = project();
+        let layout = InkLayout::new(&project);
+
+        assert_eq!(
+            layout.field("token_id").unwrap(),
+            StorageCell { key: key(0), ty: 0 }
+        );
+        assert_eq!(
+            layout.field("values[2]").unwrap(),
+            StorageCell { key: key(3), ty: 0 }
+        );
+        assert_eq!(
+            layout.field("balances").unwrap(),
+            StorageCell { key: key(4), ty: 1 }
+        );
+
+        for invalid in [
+            "values",
+            "values[3]",
+            "token_id[0]",
+            "token_id.inner",
+            "owner",
+        ] {
+            assert!(layout.field(invalid).is_err(), "{invalid}");
+        }
+
+        let labels = layout
+            .cells()
+            .into_iter()
+            .map(|(path, cell)| (path, cell.key[0]))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            labels,
+            [
+                ("token_id".to_string(), 0),
+                ("values[0]".to_string(), 1),
+                ("values[1]".to_string(), 2),
+                ("values[2]".to_string(), 3),
+                ("balances".to_string(), 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn mapping_entries_hash_the_root_key_and_the_encoded_key() {
+        let project = project();
+        let layout = InkLayout::new(&project);
+
+        // blake2_256(0x04 ++ [0; 31] ++ SCALE(7u32))
+        let expected =
+            hex::decode("0047c7dcf77884036bb3273423e6c1f7d52a04a9901adf6212ff8e006cbe5644")
+                .unwrap();
+
+        let entry = layout.mapping_entry("balances", &7u32).unwrap();
+        assert_eq!(&entry.key[..], &expected[..]);
+        assert_eq!(entry.ty, 2);
+
+        // the key must be a valid encoding of the mapping's key type
+        assert!(layout.mapping_entry("balances", &7u64).is_err());
+        assert!(layout.mapping_entry("balances", &7u16).is_err());
+        assert!(layout.mapping_entry("token_id", &7u32).is_err());
+    }
+}
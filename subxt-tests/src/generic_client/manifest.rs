@@ -0,0 +1,788 @@
+//! Declarative multi-contract deployments.
+//!
+//! A manifest lists the contracts of a "world", the messages to send once they are up and the
+//! read-only queries whose results later entries need. String arguments may reference other
+//! entries with `${name}`, which expands to the `0x`-prefixed address recorded under that name.
+//! Dev accounts (`${alice}`, `${bob}`, ...) are always available.
+//!
+//! ```toml
+//! [[contract]]
+//! name = "token"
+//! bundle = "../../contracts/ERC20.contract"
+//! append = [{ u256 = "10000000000000000000000" }]
+//!
+//! [[contract]]
+//! name = "vault"
+//! bundle = "../../contracts/vault.contract"
+//! args = ["${token}"]
+//! value = 1000000
+//!
+//! [[call]]
+//! name = "open"
+//! contract = "vault"
+//! message = "open"
+//! args = ["${bob}"]
+//! capture = { position = { event = "Opened", field = "position" } }
+//!
+//! [[query]]
+//! name = "owner"
+//! contract = "vault"
+//! message = "owner"
+//! after = ["open"]
+//! ```
+//!
+//! Entries run in dependency order: an entry waits for every name it references, plus the names
+//! listed in `after` for side effects that can't be seen from its arguments. Ties are broken by
+//! declaration order.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+use contract_transcode::{ContractMessageTranscoder, Value};
+use parity_scale_codec::{Decode, Encode};
+use serde::Deserialize;
+use sp_core::{
+    crypto::{AccountId32, Ss58Codec},
+    sr25519, Pair as _, U256,
+};
+use sp_keyring::AccountKeyring;
+
+use super::Contract;
+use crate::{node, API};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    #[serde(default, rename = "contract")]
+    pub contracts: Vec<ContractEntry>,
+    #[serde(default, rename = "call")]
+    pub calls: Vec<CallEntry>,
+    #[serde(default, rename = "query")]
+    pub queries: Vec<QueryEntry>,
+
+    /// bundles are resolved relative to the manifest
+    #[serde(skip)]
+    base: PathBuf,
+}
+
+/// A contract to deploy, to attach to at a known address, or only to upload.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ContractEntry {
+    pub name: String,
+    pub bundle: PathBuf,
+    #[serde(default = "default_constructor")]
+    pub constructor: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub append: Vec<RawArg>,
+    #[serde(default)]
+    pub value: u128,
+    /// secret uri of the signer, the executor's default caller if omitted
+    pub caller: Option<String>,
+    /// attach to an existing instance instead of deploying a new one
+    pub address: Option<String>,
+    /// only upload the code, e.g. for contracts instantiated by a factory
+    #[serde(default)]
+    pub upload_only: bool,
+    #[serde(default)]
+    pub after: Vec<String>,
+}
+
+/// A message sent once its contract is deployed.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CallEntry {
+    /// lets other entries order themselves `after` this call
+    pub name: Option<String>,
+    pub contract: String,
+    pub message: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub append: Vec<RawArg>,
+    #[serde(default)]
+    pub value: u128,
+    pub caller: Option<String>,
+    /// address-typed event fields to record in the address book
+    #[serde(default)]
+    pub capture: BTreeMap<String, Capture>,
+    #[serde(default)]
+    pub after: Vec<String>,
+}
+
+/// A dry-run whose return value is an address, recorded under `name`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QueryEntry {
+    pub name: String,
+    pub contract: String,
+    pub message: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub append: Vec<RawArg>,
+    pub caller: Option<String>,
+    #[serde(default)]
+    pub after: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Capture {
+    pub event: String,
+    pub field: String,
+}
+
+/// Arguments the transcoder can't parse, SCALE-encoded after the transcoded ones.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RawArg {
+    U256(String),
+    U128(String),
+}
+
+impl RawArg {
+    fn encode_to(&self, out: &mut Vec<u8>) -> anyhow::Result<()> {
+        match self {
+            RawArg::U256(v) => U256::from_dec_str(v)
+                .map_err(|e| anyhow::anyhow!("invalid u256 {v}: {e:?}"))?
+                .encode_to(out),
+            RawArg::U128(v) => v.parse::<u128>()?.encode_to(out),
+        }
+
+        Ok(())
+    }
+}
+
+fn default_constructor() -> String {
+    "new".to_string()
+}
+
+#[derive(Clone, Copy)]
+enum Step<'a> {
+    Contract(&'a ContractEntry),
+    Call(&'a CallEntry),
+    Query(&'a QueryEntry),
+}
+
+impl<'a> Step<'a> {
+    fn provides(&self) -> Vec<&'a str> {
+        match self {
+            Step::Contract(c) => vec![c.name.as_str()],
+            Step::Call(c) => c
+                .name
+                .iter()
+                .map(String::as_str)
+                .chain(c.capture.keys().map(String::as_str))
+                .collect(),
+            Step::Query(q) => vec![q.name.as_str()],
+        }
+    }
+
+    fn requires(&self) -> BTreeSet<String> {
+        let (strings, after, target): (Vec<&String>, _, _) = match self {
+            Step::Contract(c) => (
+                c.args.iter().chain(c.address.iter()).collect(),
+                &c.after,
+                None,
+            ),
+            Step::Call(c) => (c.args.iter().collect(), &c.after, Some(&c.contract)),
+            Step::Query(q) => (q.args.iter().collect(), &q.after, Some(&q.contract)),
+        };
+
+        strings
+            .into_iter()
+            .flat_map(|s| references(s))
+            .filter(|r| dev_account(r).is_none())
+            .chain(after.iter().cloned())
+            .chain(target.cloned())
+            .collect()
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Step::Contract(c) => format!("contract {}", c.name),
+            Step::Call(c) => format!("call {}.{}", c.contract, c.message),
+            Step::Query(q) => format!("query {}", q.name),
+        }
+    }
+}
+
+impl Manifest {
+    /// Parse a manifest, as YAML for `.yaml`/`.yml` files and as TOML otherwise.
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let raw = std::fs::read_to_string(path)?;
+
+        let mut manifest: Manifest = match path.extension().and_then(|e| e.to_str()) {
+            Some("yaml" | "yml") => serde_yaml::from_str(&raw)?,
+            _ => toml::from_str(&raw)?,
+        };
+        manifest.base = path.parent().map(Path::to_path_buf).unwrap_or_default();
+
+        Ok(manifest)
+    }
+
+    fn steps(&self) -> Vec<Step<'_>> {
+        self.contracts
+            .iter()
+            .map(Step::Contract)
+            .chain(self.calls.iter().map(Step::Call))
+            .chain(self.queries.iter().map(Step::Query))
+            .collect()
+    }
+
+    /// Order entries so that each one runs after everything it references, names in `seed` are
+    /// available from the start.
+    fn plan(&self, seed: &AddressBook) -> anyhow::Result<Vec<Step<'_>>> {
+        let steps = self.steps();
+
+        let mut provider = BTreeMap::new();
+        for (i, s) in steps.iter().enumerate() {
+            for name in s.provides() {
+                if dev_account(name).is_some() {
+                    return Err(anyhow::anyhow!("{name} is reserved for a dev account"));
+                }
+                if provider.insert(name, i).is_some() || seed.0.contains_key(name) {
+                    return Err(anyhow::anyhow!("{name} is defined more than once"));
+                }
+            }
+        }
+
+        let mut deps = Vec::with_capacity(steps.len());
+        for s in &steps {
+            let mut d = BTreeSet::new();
+            for name in s.requires() {
+                if seed.0.contains_key(&name) {
+                    continue;
+                }
+
+                let i = provider.get(name.as_str()).ok_or_else(|| {
+                    anyhow::anyhow!("{} references unknown name {name}", s.describe())
+                })?;
+                d.insert(*i);
+            }
+            deps.push(d);
+        }
+
+        let mut done = vec![false; steps.len()];
+        let mut order = Vec::with_capacity(steps.len());
+
+        while order.len() < steps.len() {
+            let next = (0..steps.len())
+                .find(|i| !done[*i] && deps[*i].iter().all(|d| done[*d]))
+                .ok_or_else(|| {
+                    let stuck = (0..steps.len())
+                        .filter(|i| !done[*i])
+                        .map(|i| steps[i].describe())
+                        .collect::<Vec<_>>();
+                    anyhow::anyhow!("dependency cycle between {}", stuck.join(", "))
+                })?;
+
+            done[next] = true;
+            order.push(steps[next]);
+        }
+
+        Ok(order)
+    }
+
+    /// Bring the world up, signing with `default_caller` unless an entry names its own.
+    pub async fn deploy(&self, api: &API, default_caller: &sr25519::Pair) -> anyhow::Result<World> {
+        self.deploy_with(api, default_caller, AddressBook::default())
+            .await
+    }
+
+    /// Like `deploy`, on top of addresses recorded elsewhere, e.g. by a shared fixture.
+    pub async fn deploy_with(
+        &self,
+        api: &API,
+        default_caller: &sr25519::Pair,
+        seed: AddressBook,
+    ) -> anyhow::Result<World> {
+        let mut world = World {
+            contracts: BTreeMap::new(),
+            addresses: seed,
+        };
+
+        for step in self.plan(&world.addresses)? {
+            match step {
+                Step::Contract(entry) => {
+                    let contract = self
+                        .deploy_contract(api, &world, entry, default_caller)
+                        .await?;
+
+                    if let Some(addr) = contract.address() {
+                        world.addresses.insert(&entry.name, addr.clone());
+                    }
+                    world.contracts.insert(entry.name.clone(), contract);
+                }
+                Step::Call(entry) => {
+                    let contract = world.contract(&entry.contract)?;
+                    let data = encode(
+                        contract,
+                        &entry.message,
+                        &world.addresses.substitute_all(&entry.args)?,
+                        &entry.append,
+                    )?;
+
+                    let out = contract
+                        .call_with_fee(
+                            api,
+                            signer(&entry.caller, default_caller)?,
+                            entry.value,
+                            |_| data.clone(),
+                        )
+                        .await?;
+
+                    let mut captured = Vec::new();
+                    for (name, capture) in &entry.capture {
+                        captured.push((name, capture_address(contract, &out.events, capture)?));
+                    }
+                    for (name, addr) in captured {
+                        world.addresses.insert(name, addr);
+                    }
+                }
+                Step::Query(entry) => {
+                    let contract = world.contract(&entry.contract)?;
+                    let data = encode(
+                        contract,
+                        &entry.message,
+                        &world.addresses.substitute_all(&entry.args)?,
+                        &entry.append,
+                    )?;
+
+                    let rv = contract
+                        .try_call(api, signer(&entry.caller, default_caller)?, 0, |_| {
+                            data.clone()
+                        })
+                        .await?;
+                    let addr = AccountId32::decode(&mut &rv[..])?;
+
+                    world.addresses.insert(&entry.name, addr);
+                }
+            }
+        }
+
+        Ok(world)
+    }
+
+    async fn deploy_contract(
+        &self,
+        api: &API,
+        world: &World,
+        entry: &ContractEntry,
+        default_caller: &sr25519::Pair,
+    ) -> anyhow::Result<Contract> {
+        let mut contract = Contract::new(self.base.join(&entry.bundle))?;
+        let caller = signer(&entry.caller, default_caller)?;
+
+        if entry.upload_only {
+            contract.upload_code(api, caller).await?;
+
+            return Ok(contract);
+        }
+
+        if let Some(addr) = &entry.address {
+            let addr = parse_address(&world.addresses.substitute(addr)?)?;
+
+            return contract.from_addr(addr);
+        }
+
+        let data = encode(
+            &contract,
+            &entry.constructor,
+            &world.addresses.substitute_all(&entry.args)?,
+            &entry.append,
+        )?;
+
+        contract
+            .deploy(api, caller, entry.value, |_| data.clone())
+            .await?;
+
+        Ok(contract)
+    }
+}
+
+/// Contracts and addresses brought up by a manifest.
+#[derive(Default)]
+pub struct World {
+    contracts: BTreeMap<String, Contract>,
+    addresses: AddressBook,
+}
+
+impl World {
+    pub fn contract(&self, name: &str) -> anyhow::Result<&Contract> {
+        self.contracts
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("no contract named {name}"))
+    }
+
+    /// move a contract out of the world, e.g. to keep it in a test fixture
+    pub fn take(&mut self, name: &str) -> anyhow::Result<Contract> {
+        self.contracts
+            .remove(name)
+            .ok_or_else(|| anyhow::anyhow!("no contract named {name}"))
+    }
+
+    pub fn addresses(&self) -> &AddressBook {
+        &self.addresses
+    }
+}
+
+/// Every address recorded while deploying, by entry or capture name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AddressBook(BTreeMap<String, AccountId32>);
+
+impl AddressBook {
+    pub fn get(&self, name: &str) -> anyhow::Result<&AccountId32> {
+        self.0
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("no address recorded for {name}"))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &AccountId32)> {
+        self.0.iter()
+    }
+
+    pub fn insert(&mut self, name: &str, addr: AccountId32) {
+        self.0.insert(name.to_string(), addr);
+    }
+
+    fn lookup(&self, name: &str) -> anyhow::Result<AccountId32> {
+        match dev_account(name) {
+            Some(k) => Ok(k.to_account_id()),
+            None => self.get(name).cloned(),
+        }
+    }
+
+    /// expand every `${name}` in `s` to the hex encoded address recorded under `name`
+    pub fn substitute(&self, s: &str) -> anyhow::Result<String> {
+        let mut out = String::with_capacity(s.len());
+        let mut rest = s;
+
+        while let Some(start) = rest.find("${") {
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| anyhow::anyhow!("unterminated reference in {s}"))?;
+
+            out.push_str(&rest[..start]);
+            let addr = self.lookup(&rest[start + 2..start + end])?;
+            out.push_str(&format!("0x{}", hex::encode(addr)));
+
+            rest = &rest[start + end + 1..];
+        }
+        out.push_str(rest);
+
+        Ok(out)
+    }
+
+    fn substitute_all(&self, args: &[String]) -> anyhow::Result<Vec<String>> {
+        args.iter().map(|a| self.substitute(a)).collect()
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::Value::Object(
+            self.0
+                .iter()
+                .map(|(k, v)| (k.clone(), serde_json::Value::String(v.to_ss58check())))
+                .collect(),
+        )
+    }
+
+    /// write the address book as a JSON object of ss58 addresses
+    pub fn write(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(&self.to_json())?)?;
+
+        Ok(())
+    }
+}
+
+fn references(s: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut rest = s;
+
+    while let Some(start) = rest.find("${") {
+        match rest[start..].find('}') {
+            Some(end) => {
+                out.push(rest[start + 2..start + end].to_string());
+                rest = &rest[start + end + 1..];
+            }
+            None => break,
+        }
+    }
+
+    out
+}
+
+fn dev_account(name: &str) -> Option<AccountKeyring> {
+    match name {
+        "alice" => Some(AccountKeyring::Alice),
+        "bob" => Some(AccountKeyring::Bob),
+        "charlie" => Some(AccountKeyring::Charlie),
+        "dave" => Some(AccountKeyring::Dave),
+        "eve" => Some(AccountKeyring::Eve),
+        "ferdie" => Some(AccountKeyring::Ferdie),
+        _ => None,
+    }
+}
+
+fn signer(suri: &Option<String>, default: &sr25519::Pair) -> anyhow::Result<sr25519::Pair> {
+    match suri {
+        Some(s) => sr25519::Pair::from_string(s, None)
+            .map_err(|e| anyhow::anyhow!("invalid suri {s}: {e:?}")),
+        None => Ok(default.clone()),
+    }
+}
+
+/// accepts both ss58 and `0x`-prefixed hex addresses
+pub fn parse_address(s: &str) -> anyhow::Result<AccountId32> {
+    if let Some(h) = s.strip_prefix("0x") {
+        let raw: [u8; 32] = hex::decode(h)?
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("expecting 32 bytes address: {s}"))?;
+
+        return Ok(raw.into());
+    }
+
+    AccountId32::from_ss58check(s).map_err(|e| anyhow::anyhow!("invalid address {s}: {e:?}"))
+}
+
+fn encode(
+    contract: &Contract,
+    name: &str,
+    args: &[String],
+    append: &[RawArg],
+) -> anyhow::Result<Vec<u8>> {
+    let mut data = ContractMessageTranscoder::new(contract.project()).encode(name, args)?;
+
+    for a in append {
+        a.encode_to(&mut data)?;
+    }
+
+    Ok(data)
+}
+
+/// find the first `capture.event` emitted by `contract` and read its address field
+fn capture_address(
+    contract: &Contract,
+    events: &[node::contracts::events::ContractEmitted],
+    capture: &Capture,
+) -> anyhow::Result<AccountId32> {
+    let transcoder = ContractMessageTranscoder::new(contract.project());
+
+    events
+        .iter()
+        .filter(|e| Some(&e.contract) == contract.address())
+        .filter_map(|e| transcoder.decode_contract_event(&mut &e.data[..]).ok())
+        .find_map(|v| match v {
+            Value::Map(m) if m.ident().as_deref() == Some(capture.event.as_str()) => {
+                m.get_by_str(&capture.field).and_then(value_to_address)
+            }
+            _ => None,
+        })
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "no {} event with an address field {}",
+                capture.event,
+                capture.field
+            )
+        })
+}
+
+fn value_to_address(v: &Value) -> Option<AccountId32> {
+    match v {
+        Value::Literal(s) | Value::String(s) => parse_address(s).ok(),
+        Value::Hex(h) => <[u8; 32]>::try_from(h.bytes()).ok().map(Into::into),
+        Value::Seq(s) => s
+            .elems()
+            .iter()
+            .map(|e| match e {
+                Value::UInt(b) => u8::try_from(*b).ok(),
+                _ => None,
+            })
+            .collect::<Option<Vec<u8>>>()
+            .and_then(|b| <[u8; 32]>::try_from(b).ok())
+            .map(Into::into),
+        Value::Tuple(t) => t.values().next().and_then(value_to_address),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(raw: &str) -> anyhow::Result<Vec<String>> {
+        let manifest: Manifest = toml::from_str(raw)?;
+
+        Ok(manifest
+            .plan(&AddressBook::default())?
+            .iter()
+            .map(Step::describe)
+            .collect())
+    }
+
+    #[test]
+    fn substitute_references() {
+        let mut book = AddressBook::default();
+        book.insert("token", AccountId32::new([7; 32]));
+
+        assert_eq!(
+            book.substitute("${token}").unwrap(),
+            format!("0x{}", "07".repeat(32))
+        );
+        assert_eq!(
+            book.substitute("[${alice}, ${token}]").unwrap(),
+            format!(
+                "[0x{}, 0x{}]",
+                hex::encode(AccountKeyring::Alice.to_account_id()),
+                "07".repeat(32)
+            )
+        );
+        assert_eq!(book.substitute("no references").unwrap(), "no references");
+
+        assert!(book.substitute("${vault}").is_err());
+        assert!(book.substitute("${token").is_err());
+    }
+
+    #[test]
+    fn diamond_dependencies_run_once_in_order() {
+        let order = plan(
+            r#"
+            [[contract]]
+            name = "router"
+            bundle = "router.contract"
+            args = ["${left}", "${right}"]
+
+            [[contract]]
+            name = "right"
+            bundle = "pair.contract"
+            args = ["${token}"]
+
+            [[contract]]
+            name = "left"
+            bundle = "pair.contract"
+            args = ["${token}", "${bob}"]
+
+            [[contract]]
+            name = "token"
+            bundle = "token.contract"
+
+            [[call]]
+            contract = "token"
+            message = "approve"
+            args = ["${router}"]
+
+            [[query]]
+            name = "owner"
+            contract = "left"
+            message = "owner"
+            after = ["router"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            order,
+            [
+                "contract token",
+                "contract right",
+                "contract left",
+                "contract router",
+                "call token.approve",
+                "query owner",
+            ]
+        );
+    }
+
+    #[test]
+    fn cycles_are_rejected() {
+        let err = plan(
+            r#"
+            [[contract]]
+            name = "token"
+            bundle = "token.contract"
+
+            [[contract]]
+            name = "a"
+            bundle = "a.contract"
+            args = ["${b}"]
+
+            [[contract]]
+            name = "b"
+            bundle = "b.contract"
+            after = ["a"]
+            "#,
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "dependency cycle between contract a, contract b"
+        );
+    }
+
+    #[test]
+    fn unknown_and_duplicate_names_are_rejected() {
+        let err = plan(
+            r#"
+            [[contract]]
+            name = "vault"
+            bundle = "vault.contract"
+            args = ["${token}"]
+            "#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "contract vault references unknown name token"
+        );
+
+        let err = plan(
+            r#"
+            [[contract]]
+            name = "token"
+            bundle = "token.contract"
+
+            [[query]]
+            name = "token"
+            contract = "token"
+            message = "owner"
+            "#,
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "token is defined more than once");
+
+        let err = plan(
+            r#"
+            [[contract]]
+            name = "alice"
+            bundle = "token.contract"
+            "#,
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "alice is reserved for a dev account");
+    }
+
+    #[test]
+    fn seeded_names_need_no_entry() {
+        let manifest: Manifest = toml::from_str(
+            r#"
+            [[contract]]
+            name = "vault"
+            bundle = "vault.contract"
+            args = ["${token}"]
+            "#,
+        )
+        .unwrap();
+
+        let mut seed = AddressBook::default();
+        seed.insert("token", AccountId32::new([7; 32]));
+
+        assert_eq!(manifest.plan(&seed).unwrap().len(), 1);
+    }
+}
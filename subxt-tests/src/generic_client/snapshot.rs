@@ -0,0 +1,273 @@
+//! contract child trie snapshots
+//!
+//! Dumps every key/value pair a contract holds at a given block, and diffs two dumps so the
+//! writes performed by a call can be inspected. pallet-contracts stores a contract's state in a
+//! child trie named after the `trie_id` in `ContractInfoOf`, with every 32 byte storage key
+//! hashed by `blake2_256`, so raw trie keys can only be labelled by hashing known keys.
+
+use std::{collections::BTreeMap, fmt::Write};
+
+use scale_info::PortableRegistry;
+use sp_core::{blake2_256, crypto::AccountId32, Bytes, H256};
+use subxt::rpc::{rpc_params, ClientT};
+
+use super::{
+    layout::{self, Decoded, InkLayout, StorageCell},
+    solang_storage::{SolType, SolangSlot},
+};
+use crate::{node, API};
+
+const CHILD_STORAGE_PREFIX: &[u8] = b":child_storage:default:";
+const PAGE_SIZE: u32 = 512;
+
+/// All key/value pairs of a contract's child trie, keyed by the hashed trie key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub at: H256,
+    pub entries: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+impl Snapshot {
+    /// Dump the storage of `contract_address` at the current best block.
+    pub async fn take(api: &API, contract_address: &AccountId32) -> anyhow::Result<Self> {
+        let at = api
+            .rpc()
+            .block_hash(None)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("unable to find best block"))?;
+
+        Self::take_at(api, contract_address, at).await
+    }
+
+    pub async fn take_at(
+        api: &API,
+        contract_address: &AccountId32,
+        at: H256,
+    ) -> anyhow::Result<Self> {
+        let key = node::storage()
+            .contracts()
+            .contract_info_of(contract_address);
+        let info = api
+            .storage()
+            .fetch(&key, Some(at))
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("{contract_address} is not a contract"))?;
+
+        let child_key = Bytes([CHILD_STORAGE_PREFIX, &info.trie_id.0].concat());
+
+        let mut entries = BTreeMap::new();
+        let mut start_key: Option<Bytes> = None;
+
+        loop {
+            let params = rpc_params![
+                child_key.clone(),
+                Bytes(vec![]),
+                PAGE_SIZE,
+                start_key.clone(),
+                at
+            ];
+            let keys: Vec<Bytes> = api
+                .rpc()
+                .client
+                .request("childstate_getKeysPaged", params)
+                .await?;
+
+            for k in &keys {
+                let params = rpc_params![child_key.clone(), k.clone(), at];
+                let value: Option<Bytes> = api
+                    .rpc()
+                    .client
+                    .request("childstate_getStorage", params)
+                    .await?;
+
+                if let Some(v) = value {
+                    entries.insert(k.0.clone(), v.0);
+                }
+            }
+
+            if keys.len() < PAGE_SIZE as usize {
+                break;
+            }
+
+            start_key = keys.last().cloned();
+        }
+
+        Ok(Self { at, entries })
+    }
+
+    /// raw value stored under an unhashed contract storage key
+    pub fn get(&self, key: &[u8; 32]) -> Option<&Vec<u8>> {
+        self.entries.get(blake2_256(key).as_slice())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Changes needed to turn `self` into `after`.
+    pub fn diff(&self, after: &Snapshot) -> StorageDiff {
+        let mut changes = BTreeMap::new();
+
+        for (k, v) in &self.entries {
+            match after.entries.get(k) {
+                None => {
+                    changes.insert(k.clone(), Change::Removed(v.clone()));
+                }
+                Some(n) if n != v => {
+                    changes.insert(k.clone(), Change::Updated(v.clone(), n.clone()));
+                }
+                _ => {}
+            }
+        }
+
+        for (k, v) in &after.entries {
+            if !self.entries.contains_key(k) {
+                changes.insert(k.clone(), Change::Inserted(v.clone()));
+            }
+        }
+
+        StorageDiff { changes }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    Inserted(Vec<u8>),
+    Removed(Vec<u8>),
+    Updated(Vec<u8>, Vec<u8>),
+}
+
+/// Storage changes between two snapshots, keyed by the hashed trie key.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StorageDiff {
+    pub changes: BTreeMap<Vec<u8>, Change>,
+}
+
+impl StorageDiff {
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// change made under an unhashed contract storage key
+    pub fn get(&self, key: &[u8; 32]) -> Option<&Change> {
+        self.changes.get(blake2_256(key).as_slice())
+    }
+
+    pub fn inserted(&self) -> impl Iterator<Item = (&Vec<u8>, &Vec<u8>)> {
+        self.changes.iter().filter_map(|(k, c)| match c {
+            Change::Inserted(v) => Some((k, v)),
+            _ => None,
+        })
+    }
+
+    pub fn removed(&self) -> impl Iterator<Item = (&Vec<u8>, &Vec<u8>)> {
+        self.changes.iter().filter_map(|(k, c)| match c {
+            Change::Removed(v) => Some((k, v)),
+            _ => None,
+        })
+    }
+
+    /// One line per change, using `labels` to name and decode the keys it knows about.
+    pub fn render(&self, labels: &Labels<'_>) -> String {
+        let mut out = String::new();
+
+        for (k, change) in &self.changes {
+            let name = labels
+                .get(k)
+                .map(|(name, _)| name.clone())
+                .unwrap_or_else(|| format!("0x{}", hex::encode(k)));
+            let show = |v: &[u8]| match labels.get(k) {
+                Some((_, decoder)) => decoder.show(v),
+                None => format!("0x{}", hex::encode(v)),
+            };
+
+            let _ = match change {
+                Change::Inserted(v) => writeln!(out, "+ {name}: {}", show(v)),
+                Change::Removed(v) => writeln!(out, "- {name}: {}", show(v)),
+                Change::Updated(a, b) => writeln!(out, "~ {name}: {} -> {}", show(a), show(b)),
+            };
+        }
+
+        out
+    }
+}
+
+enum Decoder<'a> {
+    Ink(&'a PortableRegistry, u32),
+    Solang(SolType),
+}
+
+impl Decoder<'_> {
+    fn decode(&self, raw: &[u8]) -> anyhow::Result<Decoded> {
+        match self {
+            Decoder::Ink(registry, ty) => layout::decode_all(registry, *ty, raw),
+            Decoder::Solang(ty) => ty.decode(raw),
+        }
+    }
+
+    fn show(&self, raw: &[u8]) -> String {
+        self.decode(raw)
+            .map(|d| d.to_string())
+            .unwrap_or_else(|_| format!("0x{}", hex::encode(raw)))
+    }
+}
+
+/// Names and value types of known storage keys, used to make a diff readable.
+pub struct Labels<'a> {
+    known: BTreeMap<Vec<u8>, (String, Decoder<'a>)>,
+}
+
+impl<'a> Labels<'a> {
+    /// Label every cell reachable from the storage layout without a mapping key.
+    pub fn from_layout(layout: &InkLayout<'a>) -> Self {
+        let registry = layout.registry();
+
+        let known = layout
+            .cells()
+            .into_iter()
+            .map(|(name, cell)| {
+                (
+                    blake2_256(&cell.key).to_vec(),
+                    (name, Decoder::Ink(registry, cell.ty)),
+                )
+            })
+            .collect();
+
+        Self { known }
+    }
+
+    /// Label a cell outside the static layout, e.g. a mapping entry.
+    pub fn cell(mut self, name: &str, registry: &'a PortableRegistry, cell: &StorageCell) -> Self {
+        self.known.insert(
+            blake2_256(&cell.key).to_vec(),
+            (name.to_string(), Decoder::Ink(registry, cell.ty)),
+        );
+        self
+    }
+
+    /// Label a solang storage slot.
+    pub fn slot(mut self, name: &str, slot: &SolangSlot) -> Self {
+        self.known.insert(
+            blake2_256(&slot.key).to_vec(),
+            (name.to_string(), Decoder::Solang(slot.ty.clone())),
+        );
+        self
+    }
+
+    fn get(&self, hashed_key: &[u8]) -> Option<&(String, Decoder<'a>)> {
+        self.known.get(hashed_key)
+    }
+
+    /// Decode the value of a labelled change, `None` if the key is unknown.
+    pub fn decode(&self, hashed_key: &[u8], raw: &[u8]) -> Option<anyhow::Result<Decoded>> {
+        self.get(hashed_key).map(|(_, decoder)| decoder.decode(raw))
+    }
+}
@@ -0,0 +1,605 @@
+//! solang storage slot calculator
+//!
+//! solang doesn't follow ink!'s storage layout for Substrate. Every state variable owns a
+//! sequential slot, and nested locations are derived with solidity-like rules:
+//!
+//! - value types, `bytes`/`string`, dynamic arrays and mappings take exactly one slot
+//! - fixed arrays and structs take the sum of their elements' slots
+//! - a mapping entry lives at `keccak256(slot ++ key)`
+//! - a dynamic array stores its length at `slot`, element `i` at `keccak256(slot) + i * size`
+//!
+//! Slots are 256 bit integers and become storage keys in little-endian order, which is also
+//! how keccak outputs are read back as slots. Variables that contain a mapping are not part of
+//! the metadata layout, so their slot and type have to be declared by the caller.
+
+use std::collections::BTreeMap;
+
+use anyhow::anyhow;
+use ink_metadata::{layout::Layout, InkProject};
+use scale_info::{PortableRegistry, TypeDef, TypeDefPrimitive};
+use sp_core::{keccak_256, U256};
+
+use super::layout::Decoded;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    Uint,
+    Int,
+    Bool,
+    Address,
+    FixedBytes,
+    Enum,
+}
+
+/// The storage shape of a solidity type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolType {
+    /// a value type of `width` bytes stored in a single slot
+    Value {
+        kind: ValueKind,
+        width: usize,
+    },
+    Bytes,
+    String,
+    Mapping(Box<SolType>, Box<SolType>),
+    DynArray(Box<SolType>),
+    FixedArray(U256, Box<SolType>),
+    Struct(Vec<(String, SolType)>),
+}
+
+impl SolType {
+    pub fn uint(bits: usize) -> Self {
+        SolType::Value {
+            kind: ValueKind::Uint,
+            width: bits / 8,
+        }
+    }
+
+    pub fn int(bits: usize) -> Self {
+        SolType::Value {
+            kind: ValueKind::Int,
+            width: bits / 8,
+        }
+    }
+
+    pub fn bool() -> Self {
+        SolType::Value {
+            kind: ValueKind::Bool,
+            width: 1,
+        }
+    }
+
+    pub fn address() -> Self {
+        SolType::Value {
+            kind: ValueKind::Address,
+            width: 32,
+        }
+    }
+
+    pub fn fixed_bytes(len: usize) -> Self {
+        SolType::Value {
+            kind: ValueKind::FixedBytes,
+            width: len,
+        }
+    }
+
+    pub fn enumeration() -> Self {
+        SolType::Value {
+            kind: ValueKind::Enum,
+            width: 1,
+        }
+    }
+
+    pub fn mapping(key: SolType, value: SolType) -> Self {
+        SolType::Mapping(Box::new(key), Box::new(value))
+    }
+
+    pub fn array(elem: SolType) -> Self {
+        SolType::DynArray(Box::new(elem))
+    }
+
+    pub fn fixed_array(len: impl Into<U256>, elem: SolType) -> Self {
+        SolType::FixedArray(len.into(), Box::new(elem))
+    }
+
+    pub fn structure<'a>(fields: impl IntoIterator<Item = (&'a str, SolType)>) -> Self {
+        SolType::Struct(
+            fields
+                .into_iter()
+                .map(|(name, ty)| (name.to_string(), ty))
+                .collect(),
+        )
+    }
+
+    /// number of slots occupied by a value of this type
+    pub fn slots(&self) -> U256 {
+        match self {
+            SolType::FixedArray(len, elem) => *len * elem.slots(),
+            SolType::Struct(fields) => fields
+                .iter()
+                .fold(U256::zero(), |acc, (_, ty)| acc + ty.slots()),
+            _ => U256::one(),
+        }
+    }
+
+    /// Decode the raw bytes stored in this type's slot.
+    pub fn decode(&self, raw: &[u8]) -> anyhow::Result<Decoded> {
+        match self {
+            SolType::Value { kind, width } => {
+                if raw.len() != *width {
+                    return Err(anyhow!("expected {width} bytes, found {}", raw.len()));
+                }
+
+                Ok(match kind {
+                    ValueKind::Uint | ValueKind::Enum => {
+                        Decoded::UInt(U256::from_little_endian(raw))
+                    }
+                    ValueKind::Int if *width <= 16 => {
+                        // sign extend into an i128
+                        let fill = if raw[*width - 1] & 0x80 != 0 { 0xff } else { 0 };
+                        let mut buf = [fill; 16];
+                        buf[..*width].copy_from_slice(raw);
+                        Decoded::Int(i128::from_le_bytes(buf))
+                    }
+                    ValueKind::Bool => Decoded::Bool(raw[0] != 0),
+                    ValueKind::FixedBytes => Decoded::Bytes(raw.iter().rev().cloned().collect()),
+                    ValueKind::Int | ValueKind::Address => Decoded::Bytes(raw.to_vec()),
+                })
+            }
+            SolType::Bytes => Ok(Decoded::Bytes(raw.to_vec())),
+            SolType::String => Ok(Decoded::Str(String::from_utf8(raw.to_vec())?)),
+            SolType::DynArray(_) => Ok(Decoded::UInt(U256::from_little_endian(raw))),
+            _ => Err(anyhow!("{self:?} is not stored in a single slot")),
+        }
+    }
+
+    /// Encode a path literal as a mapping key of this type.
+    fn encode_key(&self, literal: &str) -> anyhow::Result<Vec<u8>> {
+        match self {
+            SolType::Value { kind, width } => {
+                let mut out = match kind {
+                    ValueKind::Bool => vec![u8::from(literal.parse::<bool>()?)],
+                    ValueKind::Address => hex::decode(literal.trim_start_matches("0x"))?,
+                    // bytesN are kept as big-endian integers, so they land reversed in memory
+                    ValueKind::FixedBytes => hex::decode(literal.trim_start_matches("0x"))?
+                        .into_iter()
+                        .rev()
+                        .collect(),
+                    _ => {
+                        let mut buf = [0u8; 32];
+                        parse_int(literal)?.to_little_endian(&mut buf);
+                        buf.to_vec()
+                    }
+                };
+
+                if !matches!(kind, ValueKind::Address | ValueKind::FixedBytes) {
+                    out.truncate(*width);
+                }
+
+                if out.len() != *width {
+                    return Err(anyhow!("`{literal}` is not a {width} byte key"));
+                }
+
+                Ok(out)
+            }
+            SolType::Bytes | SolType::String => match literal.strip_prefix('"') {
+                Some(s) => Ok(s.trim_end_matches('"').as_bytes().to_vec()),
+                None => Ok(hex::decode(literal.trim_start_matches("0x"))?),
+            },
+            _ => Err(anyhow!("{self:?} can't be used as a mapping key")),
+        }
+    }
+}
+
+/// parse a decimal or `0x` prefixed literal, negative numbers in two's complement
+fn parse_int(literal: &str) -> anyhow::Result<U256> {
+    let (negative, digits) = match literal.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, literal),
+    };
+
+    let value = match digits.strip_prefix("0x") {
+        Some(hex) => U256::from_str_radix(hex, 16).map_err(|e| anyhow!("{e:?}"))?,
+        None => U256::from_dec_str(digits).map_err(|e| anyhow!("{e:?}"))?,
+    };
+
+    Ok(if negative {
+        (!value).overflowing_add(U256::one()).0
+    } else {
+        value
+    })
+}
+
+fn slot_key(slot: U256) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    slot.to_little_endian(&mut out);
+    out
+}
+
+fn keccak_slot(data: &[u8]) -> U256 {
+    U256::from_little_endian(&keccak_256(data))
+}
+
+enum Segment<'a> {
+    Field(&'a str),
+    Index(&'a str),
+}
+
+/// split `arrays[1].map["key"]` into fields and bracketed literals
+fn parse_path(path: &str) -> anyhow::Result<Vec<Segment<'_>>> {
+    let mut segments = Vec::new();
+    let mut rest = path;
+
+    while !rest.is_empty() {
+        if let Some(inner) = rest.strip_prefix('[') {
+            let mut quoted = false;
+            let end = inner
+                .char_indices()
+                .find(|(_, c)| {
+                    if *c == '"' {
+                        quoted = !quoted;
+                    }
+                    *c == ']' && !quoted
+                })
+                .map(|(i, _)| i)
+                .ok_or_else(|| anyhow!("unclosed `[` in `{path}`"))?;
+
+            segments.push(Segment::Index(inner[..end].trim()));
+            rest = &inner[end + 1..];
+        } else {
+            let field = rest.strip_prefix('.').unwrap_or(rest);
+            let end = field.find(|c| c == '.' || c == '[').unwrap_or(field.len());
+
+            if end == 0 {
+                return Err(anyhow!("empty field name in `{path}`"));
+            }
+
+            segments.push(Segment::Field(&field[..end]));
+            rest = &field[end..];
+        }
+    }
+
+    Ok(segments)
+}
+
+/// A storage location: the raw key and the shape of what is stored there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolangSlot {
+    pub key: [u8; 32],
+    pub ty: SolType,
+}
+
+pub struct SolangStorage {
+    vars: BTreeMap<String, (U256, SolType)>,
+}
+
+impl SolangStorage {
+    /// Seed the state variables from the metadata storage layout.
+    pub fn new(project: &InkProject) -> anyhow::Result<Self> {
+        let mut vars = BTreeMap::new();
+
+        if let Layout::Struct(root) = project.layout() {
+            for field in root.fields() {
+                if let (Some(name), Layout::Cell(cell)) = (field.name(), field.layout()) {
+                    let slot = U256::from_little_endian(cell.key().key());
+                    let ty = infer_type(project.registry(), cell.ty().id())?;
+
+                    vars.insert(name.clone(), (slot, ty));
+                }
+            }
+        }
+
+        Ok(Self { vars })
+    }
+
+    /// Declare a state variable missing from the metadata, e.g. one containing a mapping.
+    pub fn declare(mut self, name: &str, slot: impl Into<U256>, ty: SolType) -> Self {
+        self.vars.insert(name.to_string(), (slot.into(), ty));
+        self
+    }
+
+    /// Resolve a path such as `arrays[1].map[104]` to its storage key.
+    pub fn slot(&self, path: &str) -> anyhow::Result<SolangSlot> {
+        let mut segments = parse_path(path)?.into_iter();
+
+        let (mut slot, mut ty) = match segments.next() {
+            Some(Segment::Field(name)) => self
+                .vars
+                .get(name)
+                .cloned()
+                .ok_or_else(|| anyhow!("unknown state variable `{name}`"))?,
+            _ => return Err(anyhow!("`{path}` must start with a state variable")),
+        };
+
+        for segment in segments {
+            ty = match (segment, ty) {
+                (Segment::Field(name), SolType::Struct(fields)) => {
+                    let mut found = None;
+
+                    for (field, field_ty) in fields {
+                        if field == name {
+                            found = Some(field_ty);
+                            break;
+                        }
+
+                        slot += field_ty.slots();
+                    }
+
+                    found.ok_or_else(|| anyhow!("no member `{name}` in `{path}`"))?
+                }
+                (Segment::Index(key), SolType::Mapping(key_ty, value_ty)) => {
+                    let encoded = key_ty.encode_key(key)?;
+                    slot = keccak_slot(&[&slot_key(slot)[..], &encoded].concat());
+                    *value_ty
+                }
+                (Segment::Index(index), SolType::DynArray(elem)) => {
+                    let index = parse_int(index)?;
+                    slot = keccak_slot(&slot_key(slot)) + index * elem.slots();
+                    *elem
+                }
+                (Segment::Index(index), SolType::FixedArray(len, elem)) => {
+                    let index = parse_int(index)?;
+
+                    if index >= len {
+                        return Err(anyhow!("index {index} out of bounds in `{path}`"));
+                    }
+
+                    slot += index * elem.slots();
+                    *elem
+                }
+                (Segment::Field(name), ty) => {
+                    return Err(anyhow!("`{name}` is not a member of {ty:?}"))
+                }
+                (Segment::Index(index), ty) => return Err(anyhow!("[{index}] can't index {ty:?}")),
+            };
+        }
+
+        Ok(SolangSlot {
+            key: slot_key(slot),
+            ty,
+        })
+    }
+}
+
+/// map a solang metadata type to its storage shape
+fn infer_type(registry: &PortableRegistry, id: u32) -> anyhow::Result<SolType> {
+    let ty = registry
+        .resolve(id)
+        .ok_or_else(|| anyhow!("type {id} not found in registry"))?;
+
+    let is_byte = |id: u32| {
+        matches!(
+            registry.resolve(id).map(|t| t.type_def()),
+            Some(TypeDef::Primitive(TypeDefPrimitive::U8))
+        )
+    };
+
+    Ok(match ty.type_def() {
+        TypeDef::Primitive(p) => match p {
+            TypeDefPrimitive::Bool => SolType::bool(),
+            TypeDefPrimitive::Str => SolType::String,
+            TypeDefPrimitive::U8 => SolType::uint(8),
+            TypeDefPrimitive::U16 => SolType::uint(16),
+            TypeDefPrimitive::U32 => SolType::uint(32),
+            TypeDefPrimitive::U64 => SolType::uint(64),
+            TypeDefPrimitive::U128 => SolType::uint(128),
+            TypeDefPrimitive::U256 => SolType::uint(256),
+            TypeDefPrimitive::I8 => SolType::int(8),
+            TypeDefPrimitive::I16 => SolType::int(16),
+            TypeDefPrimitive::I32 => SolType::int(32),
+            TypeDefPrimitive::I64 => SolType::int(64),
+            TypeDefPrimitive::I128 => SolType::int(128),
+            TypeDefPrimitive::I256 => SolType::int(256),
+            TypeDefPrimitive::Char => return Err(anyhow!("solidity has no char type")),
+        },
+        TypeDef::Sequence(s) if is_byte(s.type_param().id()) => SolType::Bytes,
+        TypeDef::Sequence(s) => SolType::array(infer_type(registry, s.type_param().id())?),
+        // bytesN is described as a byte array in the metadata
+        TypeDef::Array(a) if is_byte(a.type_param().id()) && a.len() <= 32 => {
+            SolType::fixed_bytes(a.len() as usize)
+        }
+        TypeDef::Array(a) => {
+            SolType::fixed_array(a.len(), infer_type(registry, a.type_param().id())?)
+        }
+        TypeDef::Composite(_)
+            if ty.path().segments().last().map(String::as_str) == Some("AccountId") =>
+        {
+            SolType::address()
+        }
+        TypeDef::Composite(c) => SolType::Struct(
+            c.fields()
+                .iter()
+                .map(|f| {
+                    Ok((
+                        f.name().cloned().unwrap_or_default(),
+                        infer_type(registry, f.ty().id())?,
+                    ))
+                })
+                .collect::<anyhow::Result<_>>()?,
+        ),
+        TypeDef::Variant(_) => SolType::enumeration(),
+        other => return Err(anyhow!("unsupported storage type {other:?}")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// ```solidity
+    /// uint64 number;                                  // slot 0
+    /// struct Pair { uint256 a; bool[3] b; string c; }
+    /// Pair pair;                                      // slots 1..=5
+    /// struct Point { uint32 x; uint32 y; }
+    /// Point[] list;                                   // slot 6
+    /// mapping(address => uint128) balances;           // slot 7
+    /// mapping(int64 => uint8) byKey;                  // slot 8
+    /// mapping(string => bool) byName;                 // slot 9
+    /// mapping(bytes4 => bool) byHash;                 // slot 10
+    /// struct S { mapping(uint256 => uint256) a; }
+    /// S[] map;                                        // slot 11
+    /// ```
+    fn storage() -> SolangStorage {
+        SolangStorage {
+            vars: BTreeMap::new(),
+        }
+        .declare("number", 0_u64, SolType::uint(64))
+        .declare(
+            "pair",
+            1_u64,
+            SolType::structure([
+                ("a", SolType::uint(256)),
+                ("b", SolType::fixed_array(3_u64, SolType::bool())),
+                ("c", SolType::String),
+            ]),
+        )
+        .declare(
+            "list",
+            6_u64,
+            SolType::array(SolType::structure([
+                ("x", SolType::uint(32)),
+                ("y", SolType::uint(32)),
+            ])),
+        )
+        .declare(
+            "balances",
+            7_u64,
+            SolType::mapping(SolType::address(), SolType::uint(128)),
+        )
+        .declare(
+            "byKey",
+            8_u64,
+            SolType::mapping(SolType::int(64), SolType::uint(8)),
+        )
+        .declare(
+            "byName",
+            9_u64,
+            SolType::mapping(SolType::String, SolType::bool()),
+        )
+        .declare(
+            "byHash",
+            10_u64,
+            SolType::mapping(SolType::fixed_bytes(4), SolType::bool()),
+        )
+        .declare(
+            "map",
+            11_u64,
+            SolType::array(SolType::structure([(
+                "a",
+                SolType::mapping(SolType::uint(256), SolType::uint(256)),
+            )])),
+        )
+    }
+
+    #[test]
+    fn sequential_slots() {
+        let storage = storage();
+
+        for (path, slot) in [
+            ("number", 0_u64),
+            ("pair.a", 1),
+            ("pair.b[0]", 2),
+            ("pair.b[2]", 4),
+            ("pair.c", 5),
+            ("list", 6),
+            ("map", 11),
+        ] {
+            assert_eq!(
+                storage.slot(path).unwrap().key,
+                slot_key(slot.into()),
+                "{path}"
+            );
+        }
+
+        assert_eq!(storage.slot("pair.c").unwrap().ty, SolType::String);
+        assert_eq!(
+            SolType::fixed_array(3_u64, SolType::structure([("a", SolType::bool())])).slots(),
+            U256::from(3)
+        );
+    }
+
+    #[test]
+    fn hashed_slots() {
+        let storage = storage();
+
+        // keccak256 over the little-endian slot and the key, read back as a little-endian slot
+        for (path, key) in [
+            // keccak256(slot 6) + 2 * 2 + 1
+            (
+                "list[2].y",
+                "fef0cc7e9e8beff8ce47b8fbdfe0d612e8d6c80ec53e2b4b051c2566365c81e3",
+            ),
+            (
+                "list[0].x",
+                "f9f0cc7e9e8beff8ce47b8fbdfe0d612e8d6c80ec53e2b4b051c2566365c81e3",
+            ),
+            (
+                "balances[0x0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20]",
+                "227b545361eef3dcd3e0ac205d00d247d501c3b2e213900dd1db172483c2a1d5",
+            ),
+            // int64 keys take 8 little-endian bytes, negative ones in two's complement
+            (
+                "byKey[258]",
+                "98079776d5f2ddd302d4e4f4a8620c0bfe9d99a6af54d714f403ee69a852eed1",
+            ),
+            (
+                "byKey[-1]",
+                "e2be3e3b7e064f3aba064801d987eb34687834fca60ffdf1c2cd4c622def5810",
+            ),
+            (
+                "byName[\"laguna\"]",
+                "e2b03792e6529f4139025fa825268861c8719de38be91fb3918d5715956185c9",
+            ),
+            // bytes4 keys are reversed
+            (
+                "byHash[0x01020304]",
+                "e6a770a167a6a7049cd98535d4bfde3f92bb386ae3088c2b18c33c23803cd746",
+            ),
+            // keccak256((keccak256(slot 11) + 1) ++ 602)
+            (
+                "map[1].a[602]",
+                "3abc14bac3bdc56eb6fbd443e0b917e60195c8a5b0194e9043ec333065ac9d7a",
+            ),
+        ] {
+            let slot = storage.slot(path).unwrap();
+            assert_eq!(hex::encode(slot.key), key, "{path}");
+        }
+    }
+
+    #[test]
+    fn invalid_paths() {
+        let storage = storage();
+
+        for path in [
+            "missing",
+            "pair.d",
+            "pair.b[3]",
+            "number[0]",
+            "number.a",
+            "balances[0x01]",
+            "byHash[0x0102]",
+            "byName[\"laguna\"",
+            "[0]",
+        ] {
+            assert!(storage.slot(path).is_err(), "{path}");
+        }
+    }
+
+    #[test]
+    fn values_are_little_endian() {
+        assert_eq!(
+            SolType::uint(64).decode(&[2, 1, 0, 0, 0, 0, 0, 0]).unwrap(),
+            Decoded::UInt(U256::from(258))
+        );
+        assert_eq!(
+            SolType::int(16).decode(&[0xfe, 0xff]).unwrap(),
+            Decoded::Int(-2)
+        );
+        assert_eq!(
+            SolType::fixed_bytes(4).decode(&[4, 3, 2, 1]).unwrap(),
+            Decoded::Bytes(vec![1, 2, 3, 4])
+        );
+        assert_eq!(SolType::bool().decode(&[1]).unwrap(), Decoded::Bool(true));
+        assert!(SolType::uint(64).decode(&[1]).is_err());
+    }
+}
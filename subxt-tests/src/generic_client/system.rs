@@ -0,0 +1,58 @@
+//! System contracts: instances deployed by root through `SystemContractDeployer`, optionally at a
+//! chosen address, and whose code root can replace in place.
+
+use sp_core::crypto::AccountId32;
+use subxt::{tx::TxEvents, PolkadotConfig};
+
+use crate::{node, API};
+
+/// Well-known address of `env_utils`, kept across runtime upgrades.
+///
+/// Sequential system contract addresses count up from `0x00..01`, the well-known ones count
+/// down from `0xff..ff` so the two never meet.
+pub const ENV_UTILS: [u8; 32] = well_known(0);
+
+/// Well-known address of `native_token_wrapper`.
+pub const NATIVE_TOKEN_WRAPPER: [u8; 32] = well_known(1);
+
+const fn well_known(index: u8) -> [u8; 32] {
+    let mut addr = [0xff; 32];
+    addr[31] = 0xff - index;
+    addr
+}
+
+pub async fn is_system_contract(api: &API, address: &AccountId32) -> anyhow::Result<bool> {
+    let key = node::storage()
+        .system_contract_deployer()
+        .system_contracts(address);
+
+    Ok(api.storage().fetch(&key, None).await?.is_some())
+}
+
+/// Every address registered by `SystemContractDeployer`.
+pub async fn system_contracts(api: &API) -> anyhow::Result<Vec<AccountId32>> {
+    let root = node::storage()
+        .system_contract_deployer()
+        .system_contracts_root();
+    let mut iter = api.storage().iter(root, 64, None).await?;
+
+    let mut out = Vec::new();
+    while let Some((key, _)) = iter.next().await? {
+        // blake2_128_concat: the account follows a 16 bytes hash at the very end of the key
+        let raw = <[u8; 32]>::try_from(&key.0[key.0.len() - 32..])?;
+        out.push(raw.into());
+    }
+
+    Ok(out)
+}
+
+/// Fail unless the call wrapped by `sudo` dispatched successfully.
+pub(crate) fn ensure_sudid(events: &TxEvents<PolkadotConfig>) -> anyhow::Result<()> {
+    match events.find_first::<node::sudo::events::Sudid>()? {
+        Some(node::sudo::events::Sudid {
+            sudo_result: Err(e),
+        }) => Err(anyhow::anyhow!("sudo call failed: {e:?}")),
+        Some(_) => Ok(()),
+        None => Err(anyhow::anyhow!("unable to find Sudid")),
+    }
+}
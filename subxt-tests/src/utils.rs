@@ -1,19 +1,18 @@
+pub mod balance;
+pub mod mapping;
+
 use anyhow::Result;
 use k256::{elliptic_curve::sec1::ToEncodedPoint, PublicKey};
 use sp_core::keccak_256;
 use sp_core::{crypto::AccountId32, ecdsa::Public, ByteArray, H160};
 
-use crate::{
-    node::{self, runtime_types::primitives::currency::CurrencyId},
-    API,
-};
+pub use balance::{balance_of, native_currency, Balance, BalanceTracker};
+pub use mapping::{account_id_to_eth, default_account_id, eth_to_account_id};
+
+use crate::API;
 
 pub async fn free_balance_of(api: &API, addr: AccountId32) -> anyhow::Result<u128> {
-    let key = node::storage().tokens().accounts(
-        addr,
-        CurrencyId::NativeToken(node::runtime_types::primitives::currency::TokenId::Laguna),
-    );
-    let val = api.storage().fetch_or_default(&key, None).await?;
+    let val = balance_of(api, &addr, &native_currency()).await?;
 
     Ok(val.free)
 }
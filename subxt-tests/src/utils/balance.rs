@@ -0,0 +1,423 @@
+//! balances of any currency, and a tracker reporting how an operation moved them
+//!
+//! Every currency, including the native one, lives in orml `Tokens::Accounts`. A change of an
+//! account's free balance mixes up three things: the value actually transferred, the
+//! transaction fee and the storage deposit of pallet-contracts. The tracker keeps them apart:
+//! fees and deposits paid are taken from the events of every block between the snapshot and the
+//! report, while deposits held by a contract are read from its `ContractInfoOf`.
+
+use std::collections::BTreeMap;
+
+use parity_scale_codec::Encode;
+use sp_core::{crypto::AccountId32, H256};
+
+use crate::{
+    node::{
+        self,
+        runtime_types::primitives::currency::{CurrencyId, TokenId},
+    },
+    API,
+};
+
+pub fn native_currency() -> CurrencyId {
+    CurrencyId::NativeToken(TokenId::Laguna)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Balance {
+    pub free: u128,
+    pub reserved: u128,
+    pub frozen: u128,
+}
+
+impl Balance {
+    pub fn total(&self) -> u128 {
+        self.free + self.reserved
+    }
+}
+
+pub async fn balance_of(
+    api: &API,
+    addr: &AccountId32,
+    currency: &CurrencyId,
+) -> anyhow::Result<Balance> {
+    balance_at(api, addr, currency, None).await
+}
+
+async fn balance_at(
+    api: &API,
+    addr: &AccountId32,
+    currency: &CurrencyId,
+    at: Option<H256>,
+) -> anyhow::Result<Balance> {
+    let key = node::storage().tokens().accounts(addr, currency);
+    let val = api.storage().fetch_or_default(&key, at).await?;
+
+    Ok(Balance {
+        free: val.free,
+        reserved: val.reserved,
+        frozen: val.frozen,
+    })
+}
+
+/// storage deposit currently held by a contract, `None` if `addr` is not a contract
+pub async fn storage_deposit_of(api: &API, addr: &AccountId32) -> anyhow::Result<Option<u128>> {
+    storage_deposit_at(api, addr, None).await
+}
+
+async fn storage_deposit_at(
+    api: &API,
+    addr: &AccountId32,
+    at: Option<H256>,
+) -> anyhow::Result<Option<u128>> {
+    let key = node::storage().contracts().contract_info_of(addr);
+    let info = api.storage().fetch(&key, at).await?;
+
+    Ok(info.map(|i| i.storage_deposit))
+}
+
+/// How the balance of one account in one currency changed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Delta {
+    pub free: i128,
+    pub reserved: i128,
+    pub frozen: i128,
+    /// transaction fees paid by the account
+    pub fee: u128,
+    /// storage deposits paid by the account, net of the refunds it received
+    pub deposit_paid: i128,
+    /// change of the storage deposit held by the account, if it is a contract
+    pub deposit_held: i128,
+}
+
+impl Delta {
+    /// Free balance change caused by value transfers alone, i.e. with fees and storage
+    /// deposits paid added back.
+    pub fn transferred(&self) -> i128 {
+        self.free + self.fee as i128 + self.deposit_paid
+    }
+}
+
+struct Entry {
+    account: AccountId32,
+    currency: usize,
+    before: Balance,
+    deposit_before: Option<u128>,
+}
+
+/// Best block number and hash
+async fn best_block(api: &API) -> anyhow::Result<(u32, H256)> {
+    let header = api
+        .rpc()
+        .header(None)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("unable to find best block"))?;
+
+    Ok((header.number, header.hash()))
+}
+
+/// Native currency movements that tell fees and storage deposits apart
+enum Movement {
+    Fee {
+        who: AccountId32,
+        amount: u128,
+    },
+    Transfer {
+        from: AccountId32,
+        to: AccountId32,
+        amount: u128,
+    },
+    Reserved {
+        who: AccountId32,
+        amount: u128,
+    },
+    Unreserved {
+        who: AccountId32,
+        amount: u128,
+    },
+}
+
+async fn movements_at(api: &API, at: H256) -> anyhow::Result<Vec<Movement>> {
+    let native = native_currency().encode();
+    let mut movements = Vec::new();
+
+    for e in api.events().at(Some(at)).await?.iter() {
+        let e = e?;
+
+        if let Some(e) = e.as_event::<node::transaction_payment::events::TransactionFeePaid>()? {
+            movements.push(Movement::Fee {
+                who: e.who,
+                amount: e.actual_fee,
+            });
+        } else if let Some(e) = e.as_event::<node::tokens::events::Transfer>()? {
+            if e.currency_id.encode() == native {
+                movements.push(Movement::Transfer {
+                    from: e.from,
+                    to: e.to,
+                    amount: e.amount,
+                });
+            }
+        } else if let Some(e) = e.as_event::<node::tokens::events::Reserved>()? {
+            if e.currency_id.encode() == native {
+                movements.push(Movement::Reserved {
+                    who: e.who,
+                    amount: e.amount,
+                });
+            }
+        } else if let Some(e) = e.as_event::<node::tokens::events::Unreserved>()? {
+            if e.currency_id.encode() == native {
+                movements.push(Movement::Unreserved {
+                    who: e.who,
+                    amount: e.amount,
+                });
+            }
+        }
+    }
+
+    Ok(movements)
+}
+
+/// Fees and net storage deposits paid per account.
+///
+/// pallet-contracts charges a storage deposit by transferring it to the contract, which reserves
+/// it right away, and refunds it the other way around. Code deposits are reserved by the
+/// uploader itself.
+fn fees_and_deposits(
+    movements: &[Movement],
+) -> (BTreeMap<AccountId32, u128>, BTreeMap<AccountId32, i128>) {
+    let mut fees = BTreeMap::<_, u128>::new();
+    let mut deposits = BTreeMap::<_, i128>::new();
+
+    for (i, movement) in movements.iter().enumerate() {
+        let next = movements.get(i + 1);
+
+        match (movement, next) {
+            (Movement::Fee { who, amount }, _) => *fees.entry(who.clone()).or_default() += amount,
+            (
+                Movement::Transfer { from, to, amount },
+                Some(Movement::Reserved {
+                    who,
+                    amount: reserved,
+                }),
+            ) if to == who && amount == reserved => {
+                *deposits.entry(from.clone()).or_default() += *amount as i128
+            }
+            (
+                Movement::Unreserved { who, amount },
+                Some(Movement::Transfer {
+                    from,
+                    to,
+                    amount: refunded,
+                }),
+            ) if from == who && amount == refunded => {
+                *deposits.entry(to.clone()).or_default() -= *amount as i128
+            }
+            (Movement::Reserved { who, amount }, _) => {
+                let charged = matches!(
+                    i.checked_sub(1).map(|p| &movements[p]),
+                    Some(Movement::Transfer { to, amount: moved, .. }) if to == who && moved == amount
+                );
+
+                if !charged {
+                    *deposits.entry(who.clone()).or_default() += *amount as i128
+                }
+            }
+            (Movement::Unreserved { who, amount }, _) => {
+                *deposits.entry(who.clone()).or_default() -= *amount as i128
+            }
+            _ => {}
+        }
+    }
+
+    (fees, deposits)
+}
+
+/// Snapshots a set of accounts in a set of currencies and reports the deltas afterwards.
+pub struct BalanceTracker {
+    currencies: Vec<CurrencyId>,
+    entries: Vec<Entry>,
+    /// the snapshot block, fees and deposits are collected from the blocks after it
+    since: (u32, H256),
+}
+
+impl BalanceTracker {
+    pub async fn snapshot(
+        api: &API,
+        accounts: impl IntoIterator<Item = AccountId32>,
+        currencies: Vec<CurrencyId>,
+    ) -> anyhow::Result<Self> {
+        let since = best_block(api).await?;
+        let mut entries = Vec::new();
+
+        for account in accounts {
+            let deposit_before = storage_deposit_at(api, &account, Some(since.1)).await?;
+
+            for (currency, id) in currencies.iter().enumerate() {
+                entries.push(Entry {
+                    before: balance_at(api, &account, id, Some(since.1)).await?,
+                    account: account.clone(),
+                    currency,
+                    deposit_before,
+                });
+            }
+        }
+
+        Ok(Self {
+            currencies,
+            entries,
+            since,
+        })
+    }
+
+    /// track the native currency only
+    pub async fn native(
+        api: &API,
+        accounts: impl IntoIterator<Item = AccountId32>,
+    ) -> anyhow::Result<Self> {
+        Self::snapshot(api, accounts, vec![native_currency()]).await
+    }
+
+    /// Deltas from the snapshot to the best block, with the fees and storage deposits paid in
+    /// between.
+    pub async fn report(&self, api: &API) -> anyhow::Result<BalanceReport> {
+        let (until, at) = best_block(api).await?;
+
+        let mut movements = Vec::new();
+        for number in self.since.0 + 1..=until {
+            let hash = api
+                .rpc()
+                .block_hash(Some(number.into()))
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("unable to find block {number}"))?;
+
+            movements.extend(movements_at(api, hash).await?);
+        }
+
+        let (fees, deposits) = fees_and_deposits(&movements);
+
+        let mut deltas = Vec::with_capacity(self.entries.len());
+
+        for e in &self.entries {
+            let currency = &self.currencies[e.currency];
+            let after = balance_at(api, &e.account, currency, Some(at)).await?;
+            let deposit_after = storage_deposit_at(api, &e.account, Some(at)).await?;
+
+            // fees and storage deposits are always paid in the native currency
+            let native = currency.encode() == native_currency().encode();
+
+            let deposit_held = if native {
+                deposit_after.unwrap_or_default() as i128
+                    - e.deposit_before.unwrap_or_default() as i128
+            } else {
+                0
+            };
+
+            deltas.push((
+                e.account.clone(),
+                currency.encode(),
+                Delta {
+                    free: after.free as i128 - e.before.free as i128,
+                    reserved: after.reserved as i128 - e.before.reserved as i128,
+                    frozen: after.frozen as i128 - e.before.frozen as i128,
+                    fee: fees
+                        .get(&e.account)
+                        .filter(|_| native)
+                        .copied()
+                        .unwrap_or_default(),
+                    deposit_paid: deposits
+                        .get(&e.account)
+                        .filter(|_| native)
+                        .copied()
+                        .unwrap_or_default(),
+                    deposit_held,
+                },
+            ));
+        }
+
+        Ok(BalanceReport { deltas })
+    }
+}
+
+pub struct BalanceReport {
+    deltas: Vec<(AccountId32, Vec<u8>, Delta)>,
+}
+
+impl BalanceReport {
+    pub fn get(&self, who: &AccountId32, currency: &CurrencyId) -> Option<&Delta> {
+        let currency = currency.encode();
+
+        self.deltas
+            .iter()
+            .find(|(a, c, _)| a == who && *c == currency)
+            .map(|(_, _, d)| d)
+    }
+
+    /// delta in the native currency
+    pub fn native(&self, who: &AccountId32) -> Option<&Delta> {
+        self.get(who, &native_currency())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deposits_are_told_apart_from_transfers() {
+        let [user, contract, other] = [1, 2, 3].map(|i| AccountId32::new([i; 32]));
+
+        let movements = [
+            Movement::Fee {
+                who: user.clone(),
+                amount: 7,
+            },
+            // value sent along with the call
+            Movement::Transfer {
+                from: user.clone(),
+                to: contract.clone(),
+                amount: 1000,
+            },
+            // storage deposit charged for the new storage items
+            Movement::Transfer {
+                from: user.clone(),
+                to: contract.clone(),
+                amount: 300,
+            },
+            Movement::Reserved {
+                who: contract.clone(),
+                amount: 300,
+            },
+            // code deposit of an upload
+            Movement::Reserved {
+                who: other.clone(),
+                amount: 50,
+            },
+            Movement::Fee {
+                who: other.clone(),
+                amount: 5,
+            },
+            // storage deposit refunded for the items removed
+            Movement::Unreserved {
+                who: contract.clone(),
+                amount: 100,
+            },
+            Movement::Transfer {
+                from: contract.clone(),
+                to: user.clone(),
+                amount: 100,
+            },
+            Movement::Fee {
+                who: user.clone(),
+                amount: 3,
+            },
+        ];
+
+        let (fees, deposits) = fees_and_deposits(&movements);
+
+        assert_eq!(
+            fees,
+            BTreeMap::from([(user.clone(), 10), (other.clone(), 5)])
+        );
+        assert_eq!(deposits, BTreeMap::from([(user, 200), (other, 50)]));
+    }
+}
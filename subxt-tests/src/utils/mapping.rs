@@ -0,0 +1,42 @@
+//! mapping between ethereum addresses and substrate accounts, as done by laguna's `EvmCompat`
+//!
+//! An H160 is mapped to the proxy account registered for it in `EvmCompat::ProxyAccount`, or else
+//! to its default account `blake2_256("evm:" ++ address)`. The default account is a hash, so only
+//! proxy accounts map back to an H160.
+
+use parity_scale_codec::{Decode, Encode};
+use sp_core::{blake2_256, crypto::AccountId32, H160};
+
+use crate::{node, API};
+
+/// Account the runtime derives for an ethereum address without a proxy account
+pub fn default_account_id(address: H160) -> AccountId32 {
+    AccountId32::new(blake2_256(&[&b"evm:"[..], address.as_bytes()].concat()))
+}
+
+/// Substrate account the runtime maps `address` to
+pub async fn eth_to_account_id(api: &API, address: H160) -> anyhow::Result<AccountId32> {
+    let key = node::storage()
+        .evm_compat()
+        .proxy_account(Decode::decode(&mut &address.encode()[..])?);
+    let proxy = api.storage().fetch(&key, None).await?;
+
+    Ok(proxy.unwrap_or_else(|| default_account_id(address)))
+}
+
+/// Ethereum address `account` is the proxy account of, if any.
+///
+/// Only available client-side: it scans every proxy account, which contracts can't do.
+pub async fn account_id_to_eth(api: &API, account: &AccountId32) -> anyhow::Result<Option<H160>> {
+    let root = node::storage().evm_compat().proxy_account_root();
+    let mut proxies = api.storage().iter(root, 32, None).await?;
+
+    while let Some((key, proxy)) = proxies.next().await? {
+        if &proxy == account {
+            // Blake2_128Concat: the encoded H160 closes the key
+            return Ok(Some(H160::from_slice(&key.0[key.0.len() - 20..])));
+        }
+    }
+
+    Ok(None)
+}